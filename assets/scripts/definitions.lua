@@ -7,3 +7,34 @@ end
 ---@param path string
 function spawn_card(path)
 end
+
+---@param id string
+function start_dialogue(id)
+end
+
+---@param text string
+function show_event_text(text)
+end
+
+---@param path string
+function show_event_image(path)
+end
+
+---Offer a choice running the event script at `script` when taken, or ending the event if empty
+---@param text string
+---@param script string
+function add_choice(text, script)
+end
+
+---@param id string
+function grant_card(id)
+end
+
+---@param resource string
+---@param amount integer
+function modify_stat(resource, amount)
+end
+
+---@param actor string
+function start_battle(actor)
+end
@@ -0,0 +1,3 @@
+show_event_text("A barrow lies sealed beneath the ice, its door carved with warnings.")
+add_choice("Break the seal.", "scripts/events/frozen_tomb_open.lua")
+add_choice("Leave the dead in peace.", "")
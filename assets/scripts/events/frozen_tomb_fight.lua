@@ -0,0 +1,2 @@
+grant_card("shield_bash")
+start_battle("frost_draugr")
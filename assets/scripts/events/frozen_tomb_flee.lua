@@ -0,0 +1,4 @@
+modify_stat("Health", -2)
+grant_card("arrow")
+show_event_text("You escape with a fistful of arrows and a frostbitten hand.")
+add_choice("Move on.", "")
@@ -0,0 +1,4 @@
+modify_stat("Health", -3)
+show_event_text("The ice bites as you force the door. Within, a draugr stirs beside its hoard.")
+add_choice("Fight for the hoard.", "scripts/events/frozen_tomb_fight.lua")
+add_choice("Snatch what you can and run.", "scripts/events/frozen_tomb_flee.lua")
@@ -0,0 +1,3 @@
+show_event_text("A ring of standing stones hums with the voices of the dead.")
+add_choice("Listen to them.", "scripts/events/spirit_stones_listen.lua")
+add_choice("Walk on.", "")
@@ -0,0 +1,4 @@
+modify_stat("Favour", 3)
+grant_card("omen")
+show_event_text("The spirits whisper of what is to come.")
+add_choice("Move on.", "")
@@ -1,2 +1 @@
-spawn_background("backgrounds/mountains/mountains4.png")
-spawn_card("punch")
+-- Realm backgrounds and locations are spawned from data/realms.json
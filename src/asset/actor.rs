@@ -3,6 +3,7 @@ use std::collections::BTreeMap;
 use bevy::prelude::*;
 use serde::{Deserialize, Serialize};
 
+use crate::asset::card::CardId;
 use crate::asset::stats::Stats;
 
 #[derive(Resource)]
@@ -11,7 +12,7 @@ pub struct ActorLibraryHandle(pub Handle<ActorLibrary>);
 #[derive(Serialize, Deserialize, Asset, TypePath)]
 pub struct ActorLibrary(pub BTreeMap<ActorId, Actor>);
 
-#[derive(Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Clone, Debug)]
 pub struct ActorId(pub String);
 
 #[derive(Serialize, Deserialize)]
@@ -21,9 +22,17 @@ pub struct Actor {
     pub stats: Stats,
     pub image: String,
     pub face_image: String,
+    #[serde(default)]
+    pub deck: Vec<CardId>,
+    /// Fate Threads granted when the player meets this actor
+    #[serde(default)]
+    pub grants: Vec<CardId>,
+    /// Hacksilver looted when this actor is defeated
+    #[serde(default)]
+    pub silver: u32,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
 pub enum ActorType {
     Player,
     Enemy,
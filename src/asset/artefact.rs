@@ -0,0 +1,43 @@
+use std::collections::BTreeMap;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::asset::card::Rarity;
+use crate::asset::effect::Passive;
+use crate::asset::rest::RestId;
+
+#[derive(Resource)]
+pub struct ArtefactLibraryHandle(pub Handle<ArtefactLibrary>);
+
+#[derive(Serialize, Deserialize, Asset, TypePath)]
+pub struct ArtefactLibrary(pub BTreeMap<ArtefactId, Artefact>);
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Clone, Debug)]
+pub struct ArtefactId(pub String);
+
+/// A relic carried through the run, its passives firing in every battle
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Artefact {
+    pub name: String,
+    pub description: String,
+    #[serde(default)]
+    pub rarity: Rarity,
+    pub image: String,
+    pub passives: Vec<Passive>,
+    /// Percentage taken off shop prices while carried
+    #[serde(default)]
+    pub discount: u32,
+    /// Extra options offered at rest sites while carried
+    #[serde(default)]
+    pub rest: Vec<RestId>,
+}
+
+/// What an enemy's card does to one of the player's artefacts when it lands
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ArtefactTheft {
+    /// Take the artefact for good, removing it from the run
+    Steal,
+    /// Silence the artefact's passives until the battle ends
+    Disable,
+}
@@ -1,8 +1,10 @@
 use std::collections::BTreeMap;
 
 use bevy::prelude::*;
+use rand::{Rng, seq::SliceRandom};
 use serde::{Deserialize, Serialize};
 
+use crate::asset::artefact::ArtefactTheft;
 use crate::asset::stats::Stats;
 
 #[derive(Resource)]
@@ -11,25 +13,162 @@ pub struct CardLibraryHandle(pub Handle<CardLibrary>);
 #[derive(Serialize, Deserialize, Asset, TypePath)]
 pub struct CardLibrary(pub BTreeMap<CardId, Card>);
 
-#[derive(Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+impl CardLibrary {
+    /// The card as it plays at the owned copy's upgrade level
+    pub fn resolve(&self, owned: &OwnedCard) -> Option<Card> {
+        self.0.get(&owned.id).map(|card| card.at_level(owned.level))
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Clone, Debug)]
 pub struct CardId(pub String);
 
-#[derive(Serialize, Deserialize)]
+/// A copy of a card in a deck, and how far along its upgrade track it is
+#[derive(Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Clone, Debug)]
+pub struct OwnedCard {
+    pub id: CardId,
+    #[serde(default)]
+    pub level: usize,
+}
+
+impl From<CardId> for OwnedCard {
+    fn from(id: CardId) -> Self {
+        Self { id, level: 0 }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct Card {
     pub name: String,
     pub card_type: CardType,
     pub cost: Stats,
     pub deal: Stats,
     pub image: String,
+    #[serde(default)]
+    pub rarity: Rarity,
+    /// Chance for the deal to land, certain if absent
+    #[serde(default)]
+    pub chance: Option<f64>,
+    #[serde(default)]
+    pub thread: Option<FateThread>,
+    /// What this card does to the player's artefacts when an enemy lands it
+    #[serde(default)]
+    pub theft: Option<ArtefactTheft>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub text: Option<String>,
+    /// Each step of the upgrade track, applied in order
+    #[serde(default)]
+    pub upgrades: Vec<CardUpgrade>,
+    /// The upgrade level this card was resolved at
+    #[serde(skip)]
+    pub level: usize,
+}
+
+impl Card {
+    pub fn max_level(&self) -> usize {
+        self.upgrades.len()
+    }
+
+    /// The card with its first `level` upgrades applied
+    pub fn at_level(&self, level: usize) -> Card {
+        let mut card = self.clone();
+        card.level = level.min(self.max_level());
+        for upgrade in &self.upgrades[..card.level] {
+            card.name = upgrade
+                .name
+                .clone()
+                .unwrap_or_else(|| format!("{}+", card.name));
+            if let Some(cost) = &upgrade.cost {
+                card.cost = cost.clone();
+            }
+            if let Some(deal) = &upgrade.deal {
+                card.deal = deal.clone();
+            }
+            if upgrade.chance.is_some() {
+                card.chance = upgrade.chance;
+            }
+            if let Some(tags) = &upgrade.tags {
+                card.tags = tags.clone();
+            }
+            if upgrade.text.is_some() {
+                card.text = upgrade.text.clone();
+            }
+        }
+        card
+    }
+}
+
+/// One step along a card's upgrade track, overriding whatever it declares
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct CardUpgrade {
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub cost: Option<Stats>,
+    #[serde(default)]
+    pub deal: Option<Stats>,
+    #[serde(default)]
+    pub chance: Option<f64>,
+    #[serde(default)]
+    pub tags: Option<Vec<String>>,
+    #[serde(default)]
+    pub text: Option<String>,
+}
+
+/// The Norns' threads, spent from the run once played
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub enum FateThread {
+    /// Rewrite the past, rewinding the battle to the start of the turn
+    Red,
+    /// Force the present, so the next random outcome goes the player's way
+    Silver,
+    /// Unlock a future, setting a run flag for the campaign map
+    Black(String),
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Rarity {
+    #[default]
+    Common,
+    Uncommon,
+    Rare,
+    Legendary,
+}
+
+impl Rarity {
+    /// Relative odds of being offered as a reward, with rare and legendary
+    /// cards boosted by a percentage bonus
+    pub fn weight(&self, rare_weight: u32) -> f64 {
+        let bonus = 1. + rare_weight as f64 / 100.;
+        match self {
+            Self::Common => 60.,
+            Self::Uncommon => 30.,
+            Self::Rare => 9. * bonus,
+            Self::Legendary => 1. * bonus,
+        }
+    }
+
+    /// Undiscounted shop price in hacksilver
+    pub fn price(&self) -> u32 {
+        match self {
+            Self::Common => 50,
+            Self::Uncommon => 75,
+            Self::Rare => 150,
+            Self::Legendary => 300,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
 pub enum CardType {
     Combat,
     Negotiation,
     Hybrid,
 }
 
+#[derive(Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug)]
 pub enum CardPlace {
     Innate,
     Deck,
@@ -38,6 +177,78 @@ pub enum CardPlace {
     Vanish,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
 pub struct Deck {
-    pub deck: BTreeMap<CardPlace, Vec<CardId>>,
+    pub deck: BTreeMap<CardPlace, Vec<OwnedCard>>,
+}
+
+impl Deck {
+    pub fn new(cards: impl IntoIterator<Item = OwnedCard>, rng: &mut impl Rng) -> Self {
+        let mut cards = cards.into_iter().collect::<Vec<_>>();
+        cards.shuffle(rng);
+        Self {
+            deck: BTreeMap::from([(CardPlace::Deck, cards)]),
+        }
+    }
+
+    pub fn cards(&self, place: CardPlace) -> &[OwnedCard] {
+        self.deck.get(&place).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    pub fn put(&mut self, place: CardPlace, card: OwnedCard) {
+        self.deck.entry(place).or_default().push(card);
+    }
+
+    pub fn take(&mut self, place: CardPlace, index: usize) -> Option<OwnedCard> {
+        let cards = self.deck.get_mut(&place)?;
+        (index < cards.len()).then(|| cards.remove(index))
+    }
+
+    /// Draw up to `count` cards without exceeding `hand_size`, shuffling the
+    /// discard pile back into the deck when it runs out
+    pub fn draw(&mut self, count: usize, hand_size: usize, rng: &mut impl Rng) -> usize {
+        let mut drawn = 0;
+        while drawn < count && self.cards(CardPlace::Hand).len() < hand_size {
+            if self.cards(CardPlace::Deck).is_empty() {
+                let mut discard = self.deck.remove(&CardPlace::Discard).unwrap_or_default();
+                if discard.is_empty() {
+                    break;
+                }
+                discard.shuffle(rng);
+                self.deck.insert(CardPlace::Deck, discard);
+            }
+            if let Some(card) = self.deck.get_mut(&CardPlace::Deck).and_then(Vec::pop) {
+                self.put(CardPlace::Hand, card);
+                drawn += 1;
+            }
+        }
+        drawn
+    }
+
+    /// Remove a copy of a card, preferring one in hand
+    pub fn remove(&mut self, card: &CardId) -> Option<OwnedCard> {
+        let mut places = vec![CardPlace::Hand];
+        places.extend(
+            self.deck
+                .keys()
+                .copied()
+                .filter(|&place| place != CardPlace::Hand),
+        );
+        for place in places {
+            if let Some(cards) = self.deck.get_mut(&place)
+                && let Some(index) = cards.iter().position(|c| c.id == *card)
+            {
+                return Some(cards.remove(index));
+            }
+        }
+        None
+    }
+
+    pub fn discard_hand(&mut self) {
+        let hand = self.deck.remove(&CardPlace::Hand).unwrap_or_default();
+        self.deck
+            .entry(CardPlace::Discard)
+            .or_default()
+            .extend(hand);
+    }
 }
@@ -0,0 +1,35 @@
+use std::collections::BTreeMap;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::asset::card::CardId;
+use crate::asset::stats::Influence;
+
+#[derive(Resource)]
+pub struct ClanLibraryHandle(pub Handle<ClanLibrary>);
+
+#[derive(Serialize, Deserialize, Asset, TypePath)]
+pub struct ClanLibrary(pub BTreeMap<Clan, ClanOath>);
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug)]
+pub enum Clan {
+    Wolf,
+    Bear,
+    Raven,
+    Deer,
+}
+
+/// What swearing an oath to a clan grants, and takes away when broken
+#[derive(Serialize, Deserialize)]
+pub struct ClanOath {
+    pub name: String,
+    pub description: String,
+    pub influence: Influence,
+    pub cards: Vec<CardId>,
+    // Run flags opening the clan's quests, cleared again if the oath is broken
+    #[serde(default)]
+    pub unlocks: Vec<String>,
+    // Card tag admitting the clan's cards to the reward pool
+    pub tag: String,
+}
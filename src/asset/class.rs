@@ -1,5 +1,67 @@
+use std::collections::BTreeMap;
+
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::asset::actor::ActorId;
+use crate::asset::artefact::ArtefactId;
+use crate::asset::card::CardId;
+use crate::asset::effect::Passive;
+use crate::asset::rest::RestId;
+use crate::asset::stats::Stats;
+use crate::run::RunState;
+use crate::run::condition::Condition;
+
+#[derive(Resource)]
+pub struct ClassLibraryHandle(pub Handle<ClassLibrary>);
 
+#[derive(Serialize, Deserialize, Asset, TypePath)]
+pub struct ClassLibrary(pub BTreeMap<ClassId, Class>);
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Clone, Debug)]
 pub struct ClassId(pub String);
 
-pub struct Class {}
+#[derive(Serialize, Deserialize)]
+pub struct Class {
+    pub name: String,
+    pub description: String,
+    pub stats: Stats,
+    pub deck: Vec<CardId>,
+    // Cards offered during the run must share one of these tags
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub passives: Vec<Passive>,
+    #[serde(default)]
+    pub artefacts: Vec<ArtefactId>,
+    // Extra options offered at rest sites
+    #[serde(default)]
+    pub rest: Vec<RestId>,
+    pub portrait: ActorId,
+    #[serde(default)]
+    pub prestige: Option<Prestige>,
+    // Prestige forms are reached mid-run and never offered at the start
+    #[serde(default)]
+    pub is_prestige: bool,
+}
+
+/// The transition from a class into its prestige form
+#[derive(Serialize, Deserialize)]
+pub struct Prestige {
+    pub into: ClassId,
+    pub conditions: Vec<Condition>,
+    // Cards swapped for stronger versions when the player chooses to reforge them
+    #[serde(default)]
+    pub upgrades: BTreeMap<CardId, CardId>,
+}
+
+impl Class {
+    pub fn can_prestige(&self, run: &RunState) -> bool {
+        self.prestige.as_ref().is_some_and(|prestige| {
+            prestige
+                .conditions
+                .iter()
+                .all(|condition| condition.met(run))
+        })
+    }
+}
@@ -0,0 +1,212 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::asset::actor::ActorId;
+use crate::asset::artefact::ArtefactId;
+use crate::asset::card::CardId;
+use crate::asset::clan::Clan;
+use crate::asset::stats::{self, Influence};
+use crate::profile::Unlock;
+use crate::run::RunState;
+use crate::run::condition::Condition;
+use crate::run::rng::RngStream;
+
+#[derive(Resource)]
+pub struct DialogueLibraryHandle(pub Handle<DialogueLibrary>);
+
+#[derive(Serialize, Deserialize, Asset, TypePath)]
+pub struct DialogueLibrary(pub BTreeMap<DialogueId, Dialogue>);
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Clone, Debug)]
+pub struct DialogueId(pub String);
+
+/// A graph of lines, entered at `start`
+#[derive(Serialize, Deserialize)]
+pub struct Dialogue {
+    pub speaker: ActorId,
+    pub start: String,
+    pub nodes: BTreeMap<String, DialogueNode>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct DialogueNode {
+    // Defaults to the dialogue's speaker
+    #[serde(default)]
+    pub speaker: Option<ActorId>,
+    pub text: String,
+    // Without choices the line simply continues to `next`, or ends the dialogue
+    #[serde(default)]
+    pub choices: Vec<DialogueChoice>,
+    #[serde(default)]
+    pub next: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct DialogueChoice {
+    pub text: String,
+    // Hidden unless every condition is met
+    #[serde(default)]
+    pub conditions: Vec<Condition>,
+    #[serde(default)]
+    pub effects: Vec<DialogueEffect>,
+    #[serde(default)]
+    pub next: Option<String>,
+    // Replaces `next` with a branch on the outcome of a roll
+    #[serde(default)]
+    pub check: Option<Check>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub enum CheckTarget {
+    Stat(stats::Resource),
+    Influence(Influence),
+}
+
+impl fmt::Display for CheckTarget {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Stat(resource) => write!(f, "{:?}", resource),
+            Self::Influence(influence) => write!(f, "{:?}", influence),
+        }
+    }
+}
+
+/// Passes when the target plus a roll of 0..=spread reaches the difficulty
+#[derive(Serialize, Deserialize)]
+pub struct Check {
+    pub target: CheckTarget,
+    pub difficulty: i64,
+    #[serde(default)]
+    pub spread: i64,
+    #[serde(default)]
+    pub success: Option<String>,
+    #[serde(default)]
+    pub failure: Option<String>,
+    #[serde(default)]
+    pub success_effects: Vec<DialogueEffect>,
+    #[serde(default)]
+    pub failure_effects: Vec<DialogueEffect>,
+}
+
+impl Check {
+    pub fn value(&self, run: &RunState) -> i64 {
+        match self.target {
+            CheckTarget::Stat(resource) => run.stats.get(resource),
+            CheckTarget::Influence(influence) => run.influence(influence),
+        }
+    }
+
+    /// Chance of passing
+    pub fn odds(&self, run: &RunState) -> f64 {
+        let spread = self.spread.max(0);
+        let needed = self.difficulty - self.value(run);
+        let passing = (spread - needed.max(0) + 1).clamp(0, spread + 1);
+        passing as f64 / (spread + 1) as f64
+    }
+
+    pub fn roll(&self, run: &mut RunState) -> bool {
+        let odds = self.odds(run);
+        run.rng.roll(RngStream::Dialogue, odds)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub enum DialogueEffect {
+    SetFlag(String),
+    ClearFlag(String),
+    GainInfluence(Influence, i64),
+    ModifyStat(stats::Resource, i64),
+    GrantThread(CardId),
+    StartBattle(ActorId),
+    StartNegotiation(ActorId),
+    /// Transform into the class's prestige form, if its conditions are met
+    Prestige {
+        upgrade_cards: bool,
+    },
+    SwearOath(Clan),
+    BreakOath,
+    GrantArtefact(ArtefactId),
+    /// Move the least upgraded copy of a card in the deck along its track
+    UpgradeCard(CardId),
+    /// Earn a permanent unlock for every later run
+    Unlock(Unlock),
+}
+
+impl DialogueChoice {
+    pub fn prestiges(&self) -> bool {
+        self.effects
+            .iter()
+            .any(|effect| matches!(effect, DialogueEffect::Prestige { .. }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::asset::class::{ClassId, ClassLibrary};
+
+    fn new_run() -> RunState {
+        let classes: ClassLibrary =
+            serde_json::from_str(include_str!("../../assets/data/classes.json")).unwrap();
+        let id = ClassId("berserker".to_string());
+        RunState::new(0, &id, &classes.0[&id])
+    }
+
+    fn check(target: CheckTarget, difficulty: i64, spread: i64) -> Check {
+        Check {
+            target,
+            difficulty,
+            spread,
+            success: None,
+            failure: None,
+            success_effects: Vec::new(),
+            failure_effects: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn odds_are_certain_once_the_difficulty_is_met() {
+        let mut run = new_run();
+        run.gain_influence(Influence::Fate, 3);
+        let target = CheckTarget::Influence(Influence::Fate);
+        assert_eq!(check(target, 3, 0).odds(&run), 1.);
+        assert_eq!(check(target, 1, 4).odds(&run), 1.);
+        assert!(check(target, 3, 0).roll(&mut run));
+    }
+
+    #[test]
+    fn odds_are_nil_beyond_the_spread() {
+        let mut run = new_run();
+        run.gain_influence(Influence::Fate, 3);
+        let target = CheckTarget::Influence(Influence::Fate);
+        assert_eq!(check(target, 4, 0).odds(&run), 0.);
+        assert_eq!(check(target, 8, 4).odds(&run), 0.);
+        // A negative spread counts as none
+        assert_eq!(check(target, 4, -2).odds(&run), 0.);
+        assert!(!check(target, 8, 4).roll(&mut run));
+    }
+
+    #[test]
+    fn odds_count_the_passing_rolls_of_the_spread() {
+        let run = new_run();
+        let health = run.stats.get(stats::Resource::Health);
+        let target = CheckTarget::Stat(stats::Resource::Health);
+        assert_eq!(check(target, health + 2, 4).odds(&run), 0.6);
+        assert_eq!(check(target, health + 4, 4).odds(&run), 0.2);
+    }
+
+    #[test]
+    fn targets_display_as_their_name() {
+        assert_eq!(
+            CheckTarget::Stat(stats::Resource::Favour).to_string(),
+            "Favour"
+        );
+        assert_eq!(
+            CheckTarget::Influence(Influence::Raven).to_string(),
+            "Raven"
+        );
+    }
+}
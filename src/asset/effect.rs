@@ -0,0 +1,32 @@
+use serde::{Deserialize, Serialize};
+
+use crate::asset::stats;
+
+/// A point in a battle where passive effects fire
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Trigger {
+    BattleStart,
+    TurnStart,
+    TurnEnd,
+    CardPlayed,
+    Victory,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum Effect {
+    /// Change one of the player's resources
+    Gain(stats::Resource, i64),
+    /// Change a resource of every standing enemy
+    Deal(stats::Resource, i64),
+    Draw(usize),
+    /// The next cards played this turn cost nothing
+    FreeCards(usize),
+}
+
+/// Effects that fire on their own whenever the trigger is reached
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Passive {
+    pub name: String,
+    pub trigger: Trigger,
+    pub effects: Vec<Effect>,
+}
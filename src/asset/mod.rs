@@ -3,24 +3,48 @@ use bevy_common_assets::json::JsonAssetPlugin;
 
 use crate::asset::{
     actor::{ActorLibrary, ActorLibraryHandle},
+    artefact::{ArtefactLibrary, ArtefactLibraryHandle},
     card::{CardLibrary, CardLibraryHandle},
+    clan::{ClanLibrary, ClanLibraryHandle},
+    class::{ClassLibrary, ClassLibraryHandle},
+    dialogue::{DialogueLibrary, DialogueLibraryHandle},
+    realm::{RealmLibrary, RealmLibraryHandle},
+    rest::{RestLibrary, RestLibraryHandle},
 };
 
 pub mod actor;
+pub mod artefact;
 pub mod card;
+pub mod clan;
 pub mod class;
+pub mod dialogue;
+pub mod effect;
 pub mod lore;
+pub mod realm;
+pub mod rest;
 pub mod stats;
 
 pub enum AssetData {
     Actors,
+    Artefacts,
     Cards,
+    Clans,
+    Classes,
+    Dialogues,
+    Realms,
+    Rests,
 }
 impl AssetData {
     pub fn asset_path(&self) -> &'static str {
         match self {
             Self::Actors => "data/actors.json",
+            Self::Artefacts => "data/artefacts.json",
             Self::Cards => "data/cards.json",
+            Self::Clans => "data/clans.json",
+            Self::Classes => "data/classes.json",
+            Self::Dialogues => "data/dialogues.json",
+            Self::Realms => "data/realms.json",
+            Self::Rests => "data/rests.json",
         }
     }
 }
@@ -29,8 +53,26 @@ fn load_asset_data(mut commands: Commands, asset_server: Res<AssetServer>) {
     let actors = ActorLibraryHandle(asset_server.load(AssetData::Actors.asset_path()));
     commands.insert_resource(actors);
 
+    let artefacts = ArtefactLibraryHandle(asset_server.load(AssetData::Artefacts.asset_path()));
+    commands.insert_resource(artefacts);
+
     let cards = CardLibraryHandle(asset_server.load(AssetData::Cards.asset_path()));
     commands.insert_resource(cards);
+
+    let clans = ClanLibraryHandle(asset_server.load(AssetData::Clans.asset_path()));
+    commands.insert_resource(clans);
+
+    let classes = ClassLibraryHandle(asset_server.load(AssetData::Classes.asset_path()));
+    commands.insert_resource(classes);
+
+    let dialogues = DialogueLibraryHandle(asset_server.load(AssetData::Dialogues.asset_path()));
+    commands.insert_resource(dialogues);
+
+    let realms = RealmLibraryHandle(asset_server.load(AssetData::Realms.asset_path()));
+    commands.insert_resource(realms);
+
+    let rests = RestLibraryHandle(asset_server.load(AssetData::Rests.asset_path()));
+    commands.insert_resource(rests);
 }
 
 #[derive(Default)]
@@ -41,9 +83,27 @@ impl Plugin for AssetDataPlugin {
         app.add_plugins(JsonAssetPlugin::<ActorLibrary>::new(&[
             AssetData::Actors.asset_path()
         ]))
+        .add_plugins(JsonAssetPlugin::<ArtefactLibrary>::new(&[
+            AssetData::Artefacts.asset_path(),
+        ]))
         .add_plugins(JsonAssetPlugin::<CardLibrary>::new(&[
             AssetData::Cards.asset_path()
         ]))
+        .add_plugins(JsonAssetPlugin::<ClanLibrary>::new(&[
+            AssetData::Clans.asset_path()
+        ]))
+        .add_plugins(JsonAssetPlugin::<ClassLibrary>::new(&[
+            AssetData::Classes.asset_path()
+        ]))
+        .add_plugins(JsonAssetPlugin::<DialogueLibrary>::new(&[
+            AssetData::Dialogues.asset_path(),
+        ]))
+        .add_plugins(JsonAssetPlugin::<RealmLibrary>::new(&[
+            AssetData::Realms.asset_path()
+        ]))
+        .add_plugins(JsonAssetPlugin::<RestLibrary>::new(&[
+            AssetData::Rests.asset_path()
+        ]))
         .add_systems(PostStartup, load_asset_data);
     }
 }
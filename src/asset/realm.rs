@@ -0,0 +1,82 @@
+use std::collections::BTreeMap;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::asset::actor::ActorId;
+use crate::asset::dialogue::DialogueId;
+use crate::campaign::map::NodeType;
+use crate::run::RunState;
+use crate::run::condition::Condition;
+
+#[derive(Resource)]
+pub struct RealmLibraryHandle(pub Handle<RealmLibrary>);
+
+#[derive(Serialize, Deserialize, Asset, TypePath)]
+pub struct RealmLibrary(pub BTreeMap<RealmId, Realm>);
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Clone, Debug, Default)]
+pub struct RealmId(pub String);
+
+#[derive(Serialize, Deserialize)]
+pub struct Realm {
+    pub name: String,
+    pub background: String,
+    #[serde(default)]
+    pub next: Option<RealmId>,
+    #[serde(default)]
+    pub requires: Vec<String>,
+    #[serde(default)]
+    pub encounters: Vec<ActorId>,
+    #[serde(default)]
+    pub conditional_encounters: Vec<ConditionalEncounters>,
+    /// The Gullveig form ambushing the player at the realm's Wellspring
+    #[serde(default)]
+    pub boss: Option<ActorId>,
+    /// Lua event scripts drawn for Event nodes with nothing else authored
+    #[serde(default)]
+    pub events: Vec<String>,
+    // An empty node list means the realm map is procedurally generated
+    #[serde(default)]
+    pub nodes: BTreeMap<String, RealmNode>,
+    #[serde(default)]
+    pub edges: Vec<(String, String)>,
+}
+
+/// Encounters only added to the table while every condition is met
+#[derive(Serialize, Deserialize)]
+pub struct ConditionalEncounters {
+    pub conditions: Vec<Condition>,
+    pub encounters: Vec<ActorId>,
+}
+
+impl Realm {
+    /// The encounter table for this run
+    pub fn encounters(&self, run: &RunState) -> Vec<ActorId> {
+        let mut encounters = self.encounters.clone();
+        for conditional in &self.conditional_encounters {
+            if conditional.conditions.iter().all(|c| c.met(run)) {
+                encounters.extend(conditional.encounters.iter().cloned());
+            }
+        }
+        encounters
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct RealmNode {
+    pub name: String,
+    pub node_type: NodeType,
+    pub layer: usize,
+    pub lane: usize,
+    #[serde(default)]
+    pub encounter: Option<ActorId>,
+    #[serde(default)]
+    pub dialogue: Option<DialogueId>,
+    #[serde(default)]
+    pub event: Option<String>,
+    #[serde(default)]
+    pub image: Option<String>,
+    #[serde(default)]
+    pub requires: Vec<String>,
+}
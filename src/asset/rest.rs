@@ -0,0 +1,37 @@
+use std::collections::BTreeMap;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::asset::dialogue::DialogueId;
+
+#[derive(Resource)]
+pub struct RestLibraryHandle(pub Handle<RestLibrary>);
+
+#[derive(Serialize, Deserialize, Asset, TypePath)]
+pub struct RestLibrary(pub BTreeMap<RestId, RestOption>);
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Clone, Debug)]
+pub struct RestId(pub String);
+
+/// One way to spend a rest site, of which the player picks one
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct RestOption {
+    pub name: String,
+    pub description: String,
+    pub action: RestAction,
+    // Offered at every rest site, rather than only when granted by the
+    // player's class or an artefact they carry
+    #[serde(default)]
+    pub always: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub enum RestAction {
+    /// Recover a percentage of max Health and Favour
+    Recover(u32),
+    /// Upgrade a card from the run deck
+    Upgrade,
+    /// Hold a dialogue with a Norn, only while a Thread Slot is free
+    Commune(DialogueId),
+}
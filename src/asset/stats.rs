@@ -4,7 +4,7 @@ use strum::{EnumIter, IntoEnumIterator};
 
 use std::collections::BTreeMap;
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub struct Stats {
     #[serde(default = "BTreeMap::new")]
     pub resources: BTreeMap<Resource, i64>,
@@ -45,7 +45,67 @@ impl Default for Stats {
     }
 }
 
-#[derive(Serialize, Deserialize)]
+impl Stats {
+    pub fn get(&self, resource: Resource) -> i64 {
+        self.resources.get(&resource).copied().unwrap_or(0)
+    }
+
+    pub fn max(&self, resource: Resource) -> Option<i64> {
+        self.max_resources.get(&resource).map(|&max| max as i64)
+    }
+
+    pub fn sleight_of_hand(&self, sleight_of_hand: SleightOfHand) -> u16 {
+        self.sleight_of_hand
+            .get(&sleight_of_hand)
+            .copied()
+            .unwrap_or(0)
+    }
+
+    pub fn can_afford(&self, cost: &Stats) -> bool {
+        cost.resources
+            .iter()
+            .all(|(&resource, &amount)| self.get(resource) >= amount)
+    }
+
+    pub fn pay(&mut self, cost: &Stats) {
+        for (&resource, &amount) in &cost.resources {
+            self.modify(resource, -amount);
+        }
+    }
+
+    pub fn apply(&mut self, deal: &Stats) {
+        for (&resource, &amount) in &deal.resources {
+            self.modify(resource, amount);
+        }
+    }
+
+    /// Change a resource, capped at its maximum but free to fall below zero
+    pub fn modify(&mut self, resource: Resource, amount: i64) {
+        let value = self.get(resource) + amount;
+        let value = match self.max(resource) {
+            Some(max) => value.min(max),
+            None => value,
+        };
+        self.resources.insert(resource, value);
+    }
+
+    pub fn refill(&mut self, resource: Resource) {
+        if let Some(max) = self.max(resource) {
+            self.resources.insert(resource, max);
+        }
+    }
+
+    /// A copy with every resource refilled to its maximum
+    pub fn at_full(&self) -> Self {
+        let mut stats = self.clone();
+        for resource in Resource::iter() {
+            stats.refill(resource);
+        }
+        stats
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
 pub enum Stat {
     SleightOfHand(SleightOfHand),
     Element(Element),
@@ -55,7 +115,7 @@ pub enum Stat {
     Debuff(Debuff),
 }
 
-#[derive(EnumIter, Ord, PartialOrd, Eq, PartialEq, Serialize, Deserialize)]
+#[derive(EnumIter, Ord, PartialOrd, Eq, PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
 pub enum Resource {
     Health,
     Stamina,
@@ -63,7 +123,18 @@ pub enum Resource {
     Favour,
 }
 
-#[derive(EnumIter, Ord, PartialOrd, Eq, PartialEq, Serialize, Deserialize)]
+#[derive(EnumIter, Ord, PartialOrd, Eq, PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum Influence {
+    Wolf,   // Fear, speed, survival
+    Bear,   // Authority, strength
+    Raven,  // Prophecy, knowledge
+    Deer,   // Compassion, healing
+    Fate,   // Norn interactions
+    Shadow, // Hel and Gullveig
+    Divine, // Surviving gods
+}
+
+#[derive(EnumIter, Ord, PartialOrd, Eq, PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
 pub enum SleightOfHand {
     Inspiration,  // Draw count
     Versatility,  // Hand size
@@ -71,27 +142,27 @@ pub enum SleightOfHand {
     Recollection, // Deck max
 }
 
-#[derive(EnumIter, Ord, PartialOrd, Eq, PartialEq, Serialize, Deserialize)]
+#[derive(EnumIter, Ord, PartialOrd, Eq, PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
 pub enum Assistance {
     Strength,
 }
 
-#[derive(EnumIter, Ord, PartialOrd, Eq, PartialEq, Serialize, Deserialize)]
+#[derive(EnumIter, Ord, PartialOrd, Eq, PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
 pub enum Resistance {
     Endurance,
 }
 
-#[derive(EnumIter, Ord, PartialOrd, Eq, PartialEq, Serialize, Deserialize)]
+#[derive(EnumIter, Ord, PartialOrd, Eq, PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
 pub enum Element {
     Bludgeoning,
 }
 
-#[derive(EnumIter, Ord, PartialOrd, Eq, PartialEq, Serialize, Deserialize)]
+#[derive(EnumIter, Ord, PartialOrd, Eq, PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
 pub enum Buff {
     Overwhelm,
 }
 
-#[derive(EnumIter, Ord, PartialOrd, Eq, PartialEq, Serialize, Deserialize)]
+#[derive(EnumIter, Ord, PartialOrd, Eq, PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
 pub enum Debuff {
     Stun,
 }
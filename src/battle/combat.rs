@@ -0,0 +1,565 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use bevy::prelude::*;
+use rand::Rng;
+use rand::seq::IndexedRandom;
+use serde::{Deserialize, Serialize};
+
+use crate::asset::actor::{Actor, ActorId};
+use crate::asset::artefact::{ArtefactId, ArtefactLibrary, ArtefactTheft};
+use crate::asset::card::{CardId, CardLibrary, CardPlace, CardType, Deck, FateThread};
+use crate::asset::effect::{Effect, Passive, Trigger};
+use crate::asset::stats::{self, SleightOfHand, Stats};
+use crate::run::RunState;
+use crate::run::rng::{RngStream, RunRng};
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BattleKind {
+    Combat,
+    Negotiation,
+}
+impl BattleKind {
+    /// The resource that ends the battle when it runs out
+    pub fn resource(&self) -> stats::Resource {
+        match self {
+            Self::Combat => stats::Resource::Health,
+            Self::Negotiation => stats::Resource::Favour,
+        }
+    }
+
+    pub fn allows(&self, card_type: CardType) -> bool {
+        matches!(
+            (self, card_type),
+            (_, CardType::Hybrid)
+                | (Self::Combat, CardType::Combat)
+                | (Self::Negotiation, CardType::Negotiation)
+        )
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BattleOutcome {
+    Victory,
+    Defeat,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Combatant {
+    pub name: String,
+    pub actor: Option<ActorId>,
+    pub stats: Stats,
+    pub deck: Vec<CardId>,
+    pub next_card: usize,
+    #[serde(default)]
+    pub silver: u32,
+}
+
+impl Combatant {
+    pub fn from_actor(id: &ActorId, actor: &Actor) -> Self {
+        Self {
+            name: actor.name.clone(),
+            actor: Some(id.clone()),
+            stats: actor.stats.at_full(),
+            deck: actor.deck.clone(),
+            next_card: 0,
+            silver: actor.silver,
+        }
+    }
+
+    /// Refill the resources spent on playing cards each turn
+    pub fn refresh(&mut self) {
+        for resource in [stats::Resource::Stamina, stats::Resource::Mana] {
+            self.stats.refill(resource);
+        }
+    }
+
+    pub fn is_defeated(&self, kind: BattleKind) -> bool {
+        self.stats.get(kind.resource()) <= 0
+    }
+}
+
+#[derive(Resource, Serialize, Deserialize, Clone, Debug)]
+pub struct Battle {
+    pub kind: BattleKind,
+    pub player: Combatant,
+    pub enemies: Vec<Combatant>,
+    pub deck: Deck,
+    pub turn: u32,
+    pub rng: RunRng,
+    /// Run flags set by Black Threads, applied to the run when the battle ends
+    pub flags: BTreeSet<String>,
+    /// Fate Threads played, removed from the run deck when the battle ends
+    pub spent: Vec<CardId>,
+    /// The battle as it stood at the start of each turn
+    pub history: Vec<Battle>,
+    pub passives: Vec<Passive>,
+    /// The artefacts still held, with their passives
+    pub artefacts: BTreeMap<ArtefactId, Vec<Passive>>,
+    /// Artefacts silenced until the battle ends
+    pub disabled: BTreeSet<ArtefactId>,
+    /// Artefacts taken by enemies, removed from the run when the battle ends
+    pub stolen: Vec<ArtefactId>,
+    /// Cards still to be played this turn at no cost
+    pub free_cards: usize,
+    /// Tallies for the run record, kept through rewinds
+    pub played: BTreeMap<CardId, u32>,
+    pub damage_dealt: i64,
+    pub damage_taken: i64,
+    /// The last card played this turn and the battle before it, while
+    /// taking that play back would reveal nothing hidden
+    pub undo: Option<(CardId, Box<Battle>)>,
+}
+
+impl Battle {
+    pub fn new(
+        kind: BattleKind,
+        run: &mut RunState,
+        enemies: Vec<Combatant>,
+        passives: Vec<Passive>,
+        cards: &CardLibrary,
+        artefacts: &ArtefactLibrary,
+    ) -> Self {
+        let mut rng = RunRng::new(run.rng.next_seed(RngStream::Battle));
+        let deck = Deck::new(
+            run.deck
+                .iter()
+                .filter(|&owned| {
+                    cards
+                        .0
+                        .get(&owned.id)
+                        .is_some_and(|card| kind.allows(card.card_type))
+                })
+                .cloned(),
+            &mut rng.stream(RngStream::Battle),
+        );
+        let held = run
+            .inventory
+            .iter()
+            .filter_map(|id| match artefacts.0.get(id) {
+                Some(artefact) => Some((id.clone(), artefact.passives.clone())),
+                None => {
+                    log::warn!(artefact = id.0.as_str(); "Missing artefact {}", id.0);
+                    None
+                }
+            })
+            .collect();
+        Self {
+            kind,
+            player: Combatant {
+                name: run.class.0.clone(),
+                actor: None,
+                stats: run.stats.clone(),
+                deck: Vec::new(),
+                next_card: 0,
+                silver: 0,
+            },
+            enemies,
+            deck,
+            turn: 0,
+            rng,
+            flags: BTreeSet::new(),
+            spent: Vec::new(),
+            history: Vec::new(),
+            passives,
+            artefacts: held,
+            disabled: BTreeSet::new(),
+            stolen: Vec::new(),
+            free_cards: 0,
+            played: BTreeMap::new(),
+            damage_dealt: 0,
+            damage_taken: 0,
+            undo: None,
+        }
+    }
+
+    /// Fire every passive listening for the trigger, from the class and from
+    /// each artefact not disabled
+    pub fn trigger(&mut self, trigger: Trigger) {
+        let artefacts = self
+            .artefacts
+            .iter()
+            .filter(|(id, _)| !self.disabled.contains(id))
+            .flat_map(|(_, passives)| passives);
+        let effects = self
+            .passives
+            .iter()
+            .chain(artefacts)
+            .filter(|passive| passive.trigger == trigger)
+            .flat_map(|passive| passive.effects.clone())
+            .collect::<Vec<_>>();
+        for effect in effects {
+            self.apply(&effect);
+        }
+    }
+
+    pub fn apply(&mut self, effect: &Effect) {
+        match effect {
+            Effect::Gain(resource, amount) => self.player.stats.modify(*resource, *amount),
+            Effect::Deal(resource, amount) => {
+                let kind = self.kind;
+                for enemy in self.enemies.iter_mut() {
+                    if !enemy.is_defeated(kind) {
+                        enemy.stats.modify(*resource, *amount);
+                    }
+                }
+            }
+            Effect::Draw(count) => {
+                self.draw(*count);
+            }
+            Effect::FreeCards(count) => self.free_cards += count,
+        }
+    }
+
+    fn draw(&mut self, count: usize) -> usize {
+        let hand_size = self
+            .player
+            .stats
+            .sleight_of_hand(SleightOfHand::Versatility) as usize;
+        let mut rng = self.rng.stream(RngStream::Battle);
+        self.deck.draw(count, hand_size, &mut rng)
+    }
+
+    /// Refill the player's per-turn resources and draw a new hand
+    pub fn start_turn(&mut self) -> usize {
+        self.turn += 1;
+        self.player.refresh();
+        if self.turn == 1 {
+            self.trigger(Trigger::BattleStart);
+        }
+        self.trigger(Trigger::TurnStart);
+        let draw = self
+            .player
+            .stats
+            .sleight_of_hand(SleightOfHand::Inspiration) as usize;
+        let drawn = self.draw(draw);
+        self.undo = None;
+
+        let mut snapshot = self.clone();
+        snapshot.history.clear();
+        self.history.push(snapshot);
+        drawn
+    }
+
+    /// Restore the battle to the start of the current turn, keeping the
+    /// history and any threads already spent
+    pub fn rewind(&mut self) -> bool {
+        let Some(mut snapshot) = self.history.last().cloned() else {
+            return false;
+        };
+        // Threads spent this turn stay spent, even though the turn is undone
+        for card in &self.spent[snapshot.spent.len()..] {
+            if let Some(owned) = snapshot.deck.remove(card) {
+                snapshot.deck.put(CardPlace::Vanish, owned);
+            }
+        }
+        snapshot.history = std::mem::take(&mut self.history);
+        snapshot.spent = std::mem::take(&mut self.spent);
+        snapshot.played = std::mem::take(&mut self.played);
+        snapshot.damage_dealt = self.damage_dealt;
+        snapshot.damage_taken = self.damage_taken;
+        *self = snapshot;
+        true
+    }
+
+    /// Take back the last card played, restoring stats and deck zones
+    pub fn undo(&mut self) -> Option<CardId> {
+        let (id, before) = self.undo.take()?;
+        let history = std::mem::take(&mut self.history);
+        *self = *before;
+        self.history = history;
+        Some(id)
+    }
+
+    pub fn play_card(
+        &mut self,
+        index: usize,
+        target: usize,
+        cards: &CardLibrary,
+    ) -> Option<CardId> {
+        let owned = self.deck.cards(CardPlace::Hand).get(index)?;
+        let id = owned.id.clone();
+        let Some(card) = cards.resolve(owned) else {
+            log::warn!(card = id.0.as_str(); "Missing asset while playing {}", id.0);
+            return None;
+        };
+        let kind = self.kind;
+        if self
+            .enemies
+            .get(target)
+            .is_none_or(|enemy| enemy.is_defeated(kind))
+        {
+            log::warn!(card = id.0.as_str(), target; "No target {} for {}", target, id.0);
+            return None;
+        }
+        let free = self.free_cards > 0;
+        if !free && !self.player.stats.can_afford(&card.cost) {
+            return None;
+        }
+        let history = std::mem::take(&mut self.history);
+        let mut before = self.clone();
+        before.undo = None;
+        self.history = history;
+
+        if free {
+            self.free_cards -= 1;
+        } else {
+            self.player.stats.pay(&card.cost);
+        }
+        let lands = match card.chance {
+            Some(chance) => self.rng.roll(RngStream::Battle, chance),
+            None => true,
+        };
+        if lands {
+            let enemy = &mut self.enemies[target];
+            enemy.stats.apply(&card.deal);
+            let amount = -card.deal.get(kind.resource()).min(0);
+            self.damage_dealt += amount;
+            log::debug!(
+                actor = enemy.name.as_str(),
+                card = id.0.as_str(),
+                amount;
+                "{} landed on {}", id.0, enemy.name
+            );
+        }
+        let played = self.deck.take(CardPlace::Hand, index)?;
+        *self.played.entry(id.clone()).or_default() += 1;
+
+        match &card.thread {
+            Some(thread) => {
+                // Spent threads stay spent
+                self.undo = None;
+                self.deck.put(CardPlace::Vanish, played);
+                self.spent.push(id.clone());
+                match thread {
+                    FateThread::Red => {
+                        self.rewind();
+                        return Some(id);
+                    }
+                    FateThread::Silver => self.rng.force(RngStream::Battle),
+                    FateThread::Black(flag) => {
+                        self.flags.insert(flag.clone());
+                    }
+                }
+            }
+            None => self.deck.put(CardPlace::Discard, played),
+        }
+        self.trigger(Trigger::CardPlayed);
+        if card.thread.is_none() {
+            // Any roll or draw advances the rng, and can't be taken back
+            self.undo = (self.rng == before.rng).then(|| (id.clone(), Box::new(before)));
+        }
+        Some(id)
+    }
+
+    /// Every standing enemy plays the next card of its deck against the player
+    pub fn enemy_turn(&mut self, cards: &CardLibrary) {
+        let kind = self.kind;
+        let mut thefts = Vec::new();
+        for enemy in self.enemies.iter_mut() {
+            if enemy.is_defeated(kind) || enemy.deck.is_empty() {
+                continue;
+            }
+            enemy.refresh();
+            let id = &enemy.deck[enemy.next_card % enemy.deck.len()];
+            enemy.next_card += 1;
+            let Some(card) = cards.0.get(id) else {
+                log::warn!(actor = enemy.name.as_str(), card = id.0.as_str(); "Missing asset while playing {}", id.0);
+                continue;
+            };
+            if kind.allows(card.card_type) && enemy.stats.can_afford(&card.cost) {
+                enemy.stats.pay(&card.cost);
+                // Enemy rolls never consume a Silver Thread's forced outcome
+                let lands = card.chance.is_none_or(|chance| {
+                    self.rng
+                        .stream(RngStream::Battle)
+                        .random_bool(chance.clamp(0., 1.))
+                });
+                if lands {
+                    self.player.stats.apply(&card.deal);
+                    let amount = -card.deal.get(kind.resource()).min(0);
+                    self.damage_taken += amount;
+                    log::debug!(
+                        actor = enemy.name.as_str(),
+                        card = id.0.as_str(),
+                        amount;
+                        "{} played {}", enemy.name, id.0
+                    );
+                    if let Some(theft) = card.theft {
+                        thefts.push(theft);
+                    }
+                }
+            }
+        }
+        for theft in thefts {
+            self.lose_artefact(theft);
+        }
+    }
+
+    /// Steal or disable one of the player's working artefacts at random
+    fn lose_artefact(&mut self, theft: ArtefactTheft) {
+        let working = self
+            .artefacts
+            .keys()
+            .filter(|id| !self.disabled.contains(id))
+            .cloned()
+            .collect::<Vec<_>>();
+        let Some(id) = working
+            .choose(&mut self.rng.stream(RngStream::Battle))
+            .cloned()
+        else {
+            return;
+        };
+        log::info!(artefact = id.0.as_str(); "{:?} {}", theft, id.0);
+        match theft {
+            ArtefactTheft::Steal => {
+                self.artefacts.remove(&id);
+                self.stolen.push(id);
+            }
+            ArtefactTheft::Disable => {
+                self.disabled.insert(id);
+            }
+        }
+    }
+
+    pub fn end_turn(&mut self) {
+        self.undo = None;
+        self.trigger(Trigger::TurnEnd);
+        self.free_cards = 0;
+        self.deck.discard_hand();
+    }
+
+    pub fn outcome(&self) -> Option<BattleOutcome> {
+        if self.player.is_defeated(self.kind) {
+            Some(BattleOutcome::Defeat)
+        } else if self
+            .enemies
+            .iter()
+            .all(|enemy| enemy.is_defeated(self.kind))
+        {
+            Some(BattleOutcome::Victory)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::asset::actor::ActorLibrary;
+    use crate::asset::card::OwnedCard;
+    use crate::asset::class::{ClassId, ClassLibrary};
+
+    fn cards() -> CardLibrary {
+        serde_json::from_str(include_str!("../../assets/data/cards.json")).unwrap()
+    }
+
+    /// A berserker holding only `deck` against a Frost Wolf, on the first turn
+    fn battle(deck: &[&str], cards: &CardLibrary) -> Battle {
+        let classes: ClassLibrary =
+            serde_json::from_str(include_str!("../../assets/data/classes.json")).unwrap();
+        let actors: ActorLibrary =
+            serde_json::from_str(include_str!("../../assets/data/actors.json")).unwrap();
+        let artefacts: ArtefactLibrary =
+            serde_json::from_str(include_str!("../../assets/data/artefacts.json")).unwrap();
+        let class = ClassId("berserker".to_string());
+        let mut run = RunState::new(11, &class, &classes.0[&class]);
+        run.deck = deck
+            .iter()
+            .map(|id| OwnedCard::from(CardId(id.to_string())))
+            .collect();
+        run.inventory.clear();
+        let wolf = ActorId("frost_wolf".to_string());
+        let enemies = vec![Combatant::from_actor(&wolf, &actors.0[&wolf])];
+        let mut battle = Battle::new(
+            BattleKind::Combat,
+            &mut run,
+            enemies,
+            Vec::new(),
+            cards,
+            &artefacts,
+        );
+        battle.start_turn();
+        battle
+    }
+
+    #[test]
+    fn enemy_turn_keeps_a_forced_roll() {
+        let cards = cards();
+        let mut battle = battle(&["punch"; 10], &cards);
+        battle.enemies[0].deck = vec![CardId("wild_swing".to_string())];
+        battle.rng.force(RngStream::Battle);
+        let before = battle.rng.clone();
+        battle.enemy_turn(&cards);
+        // The enemy rolled for its card, without spending the forced outcome
+        assert_ne!(battle.rng, before);
+        assert!(battle.rng.roll(RngStream::Battle, 0.));
+    }
+
+    fn hand(battle: &Battle) -> Vec<&str> {
+        battle
+            .deck
+            .cards(CardPlace::Hand)
+            .iter()
+            .map(|owned| owned.id.0.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn undo_restores_the_battle_exactly() {
+        let cards = cards();
+        let mut battle = battle(&["punch"; 10], &cards);
+        let before = serde_json::to_value(&battle).unwrap();
+        let played = battle.play_card(0, 0, &cards);
+        assert_eq!(played, Some(CardId("punch".to_string())));
+        assert_eq!(hand(&battle).len(), 4);
+        assert_eq!(battle.player.stats.get(stats::Resource::Stamina), 3);
+        assert_eq!(battle.enemies[0].stats.get(stats::Resource::Health), 7);
+
+        assert_eq!(battle.undo(), played);
+        assert_eq!(hand(&battle).len(), 5);
+        assert_eq!(battle.player.stats.get(stats::Resource::Stamina), 4);
+        assert_eq!(battle.enemies[0].stats.get(stats::Resource::Health), 8);
+        assert_eq!(serde_json::to_value(&battle).unwrap(), before);
+        assert_eq!(battle.undo(), None);
+    }
+
+    #[test]
+    fn a_play_that_rolls_cannot_be_undone() {
+        let cards = cards();
+        let mut battle = battle(&["wild_swing"; 10], &cards);
+        assert!(battle.play_card(0, 0, &cards).is_some());
+        assert!(battle.undo.is_none());
+        assert_eq!(battle.undo(), None);
+        assert_eq!(hand(&battle).len(), 4);
+    }
+
+    #[test]
+    fn red_thread_rewinds_the_turn_but_stays_spent() {
+        let cards = cards();
+        let mut battle = battle(&["punch", "punch", "punch", "punch", "red_thread"], &cards);
+        battle
+            .player
+            .stats
+            .resources
+            .insert(stats::Resource::Mana, 1);
+        let punch = hand(&battle).iter().position(|&id| id == "punch").unwrap();
+        battle.play_card(punch, 0, &cards);
+        assert_eq!(battle.enemies[0].stats.get(stats::Resource::Health), 7);
+
+        let thread = hand(&battle)
+            .iter()
+            .position(|&id| id == "red_thread")
+            .unwrap();
+        battle.play_card(thread, 0, &cards);
+        assert_eq!(hand(&battle), ["punch"; 4]);
+        assert_eq!(battle.player.stats.get(stats::Resource::Stamina), 4);
+        assert_eq!(battle.enemies[0].stats.get(stats::Resource::Health), 8);
+        assert_eq!(battle.deck.cards(CardPlace::Vanish).len(), 1);
+        assert_eq!(battle.spent, [CardId("red_thread".to_string())]);
+        // The run record still counts what was played before the rewind
+        assert_eq!(battle.played.values().sum::<u32>(), 2);
+        assert_eq!(battle.damage_dealt, 1);
+        assert!(battle.undo.is_none());
+    }
+}
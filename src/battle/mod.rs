@@ -1,32 +1,681 @@
+pub mod combat;
+
 use bevy::prelude::*;
 use bevy_scriptum::prelude::*;
 use bevy_scriptum::runtimes::lua::prelude::*;
+use serde::{Deserialize, Serialize};
 
-use crate::cards::DropZoneNode;
-use crate::state::AppState;
+use crate::asset::actor::{ActorLibrary, ActorLibraryHandle};
+use crate::asset::artefact::{ArtefactLibrary, ArtefactLibraryHandle};
+use crate::asset::card::{CardLibrary, CardLibraryHandle, CardPlace};
+use crate::asset::class::{ClassLibrary, ClassLibraryHandle};
+use crate::asset::effect::Trigger;
+use crate::asset::stats::{self, Stats};
+use crate::battle::combat::{Battle, BattleKind, BattleOutcome, Combatant};
+use crate::campaign::map::NodeType;
+use crate::cards::event::{NodeInteraction, NodeInteractionType};
+use crate::cards::script::SpawnedByScript;
+use crate::cards::{DropZoneNode, InteractiveNode, spawn_card_node};
+use crate::input::{Focused, InputAction};
+use crate::menu::resource::{Language, Settings};
+use crate::menu::{BACKDROP, NORMAL_BUTTON, TEXT};
+use crate::replay::{self, BattleRecording, ReplayPlayback};
+use crate::run::telemetry::{self, TelemetryEvent};
+use crate::run::{Encounter, RunState, save};
+use crate::state::{AppState, BattleState};
+use crate::zindex::Z_ENEMY;
 use crate::{HEIGHT, WIDTH};
 
-fn setup_battle_ui(mut commands: Commands, asset_server: Res<AssetServer>) {
+const HAND_Y: f32 = -HEIGHT / 2. + 32.;
+const HAND_SPACING: f32 = 40.;
+const ENEMY_Y: f32 = 48.;
+const ENEMY_SPACING: f32 = 96.;
+const ENEMY_SIZE: Vec2 = vec2(64., 96.);
+const TARGET: Color = Color::srgb(0.9, 0.3, 0.3);
+
+#[derive(Event, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub enum BattleAction {
+    PlayCard { card: usize, target: usize },
+    Undo,
+    EndTurn,
+}
+
+#[derive(Component)]
+struct OnBattleScreen;
+
+#[derive(Component)]
+struct HandCard(usize);
+
+#[derive(Component)]
+struct EnemyNode(usize);
+
+/// The enemy a card played from the keyboard or gamepad lands on
+#[derive(Resource, Default)]
+struct TargetedEnemy(usize);
+
+#[derive(Component)]
+struct PlayerStatsText;
+
+#[derive(Component)]
+struct EnemyStatsText;
+
+#[derive(Component)]
+struct UndoButton;
+
+#[derive(Component)]
+enum BattleButtonAction {
+    Undo,
+    EndTurn,
+}
+
+fn describe(stats: &Stats, resources: &[stats::Resource]) -> String {
+    resources
+        .iter()
+        .map(|&resource| match stats.max(resource) {
+            Some(max) => format!("{:?} {}/{}", resource, stats.get(resource), max),
+            None => format!("{:?} {}", resource, stats.get(resource)),
+        })
+        .collect::<Vec<_>>()
+        .join("  ")
+}
+
+/// Return to the map without moving onto the chosen node, so a battle that
+/// could not be set up is neither skipped nor won
+fn abort_battle(run: &mut RunState, app_state: &mut NextState<AppState>) {
+    run.map.selected = None;
+    app_state.set(AppState::Campaign);
+}
+
+fn setup_battle(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut run: ResMut<RunState>,
+    actors_handle: Res<ActorLibraryHandle>,
+    actors: Res<Assets<ActorLibrary>>,
+    cards_handle: Res<CardLibraryHandle>,
+    cards: Res<Assets<CardLibrary>>,
+    classes_handle: Res<ClassLibraryHandle>,
+    classes: Res<Assets<ClassLibrary>>,
+    artefacts_handle: Res<ArtefactLibraryHandle>,
+    artefacts: Res<Assets<ArtefactLibrary>>,
+    playback: Option<Res<ReplayPlayback>>,
+    mut app_state: ResMut<NextState<AppState>>,
+) {
+    // A pending encounter from dialogue takes precedence over the map node
+    let (kind, encounter) = match run.encounter.take() {
+        Some(Encounter { actor, kind }) => (kind, Some(actor)),
+        None => {
+            let node = run.map.selected.and_then(|selected| run.map.node(selected));
+            let kind = match node.map(|node| node.node_type) {
+                Some(NodeType::Negotiation) => BattleKind::Negotiation,
+                _ => BattleKind::Combat,
+            };
+            (kind, node.and_then(|node| node.encounter.clone()))
+        }
+    };
+    let enemies = match (&encounter, actors.get(actors_handle.0.id())) {
+        (Some(id), Some(actors)) => actors
+            .0
+            .get(id)
+            .map(|actor| vec![Combatant::from_actor(id, actor)])
+            .unwrap_or_default(),
+        _ => Vec::new(),
+    };
+    // With no one to fight the battle would be won outright, so it never starts
+    if enemies.is_empty() {
+        log::error!("Missing encounter {:?} while spawning battle", encounter);
+        abort_battle(&mut run, &mut app_state);
+        return;
+    }
+    let (Some(cards), Some(artefacts)) = (
+        cards.get(cards_handle.0.id()),
+        artefacts.get(artefacts_handle.0.id()),
+    ) else {
+        log::error!("Missing asset libraries while spawning battle");
+        abort_battle(&mut run, &mut app_state);
+        return;
+    };
+
     commands.spawn(Script::<LuaScript>::new(
         asset_server.load("scripts/spawn_battle.lua"),
     ));
-    commands.spawn(DropZoneNode::new(
-        vec2(0., -HEIGHT / 2. + 32.),
-        vec2(WIDTH - 32., 50.),
+    commands.spawn((
+        OnBattleScreen,
+        DropZoneNode::new(vec2(0., HAND_Y), vec2(WIDTH - 32., 50.)),
+    ));
+    for (i, enemy) in enemies.iter().enumerate() {
+        let position = vec2(
+            WIDTH / 4. + (i as f32 - (enemies.len() as f32 - 1.) / 2.) * ENEMY_SPACING,
+            ENEMY_Y,
+        );
+        let image = enemy
+            .actor
+            .as_ref()
+            .and_then(|id| actors.get(actors_handle.0.id())?.0.get(id))
+            .map(|actor| asset_server.load(actor.image.clone()))
+            .unwrap_or_default();
+        commands.spawn((
+            OnBattleScreen,
+            EnemyNode(i),
+            DropZoneNode::new(position, ENEMY_SIZE),
+            Sprite {
+                image,
+                custom_size: Some(ENEMY_SIZE),
+                ..default()
+            },
+            Transform::from_translation(position.extend(Z_ENEMY)),
+        ));
+    }
+
+    let passives = classes
+        .get(classes_handle.0.id())
+        .and_then(|classes| classes.0.get(&run.class))
+        .map(|class| class.passives.clone())
+        .unwrap_or_default();
+    for enemy in &enemies {
+        log::info!(
+            actor = enemy.actor.as_ref().map_or("", |actor| actor.0.as_str()),
+            kind:? = kind;
+            "Encountered {}", enemy.name
+        );
+    }
+    // A replay is not a new encounter of the run it was recorded from
+    if playback.is_none() {
+        telemetry::record(
+            &run,
+            TelemetryEvent::EncounterStarted {
+                enemies: enemies
+                    .iter()
+                    .filter_map(|enemy| enemy.actor.clone())
+                    .collect(),
+                kind,
+            },
+        );
+    }
+    commands.insert_resource(TargetedEnemy::default());
+    commands.insert_resource(Battle::new(
+        kind, &mut run, enemies, passives, cards, artefacts,
+    ));
+}
+
+fn setup_battle_ui(
+    mut commands: Commands,
+    language: Res<Language>,
+    asset_server: Res<AssetServer>,
+) {
+    let font = asset_server.load(language.font_path());
+    let text_font = TextFont {
+        font_size: 16.0,
+        font: font.clone(),
+        ..default()
+    };
+    let button_node = Node {
+        width: Val::Px(160.0),
+        height: Val::Px(40.0),
+        margin: UiRect::left(Val::Px(8.0)),
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        ..default()
+    };
+
+    commands.spawn((
+        Node {
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            flex_direction: FlexDirection::Column,
+            justify_content: JustifyContent::SpaceBetween,
+            padding: UiRect::all(Val::Px(12.0)),
+            ..default()
+        },
+        OnBattleScreen,
+        children![
+            (
+                Node {
+                    width: Val::Percent(100.0),
+                    justify_content: JustifyContent::SpaceBetween,
+                    ..default()
+                },
+                children![
+                    (
+                        Text::default(),
+                        PlayerStatsText,
+                        text_font.clone(),
+                        TextColor(TEXT),
+                        BackgroundColor(BACKDROP),
+                    ),
+                    (
+                        Text::default(),
+                        EnemyStatsText,
+                        text_font.clone(),
+                        TextColor(TEXT),
+                        BackgroundColor(BACKDROP),
+                    ),
+                ]
+            ),
+            (
+                Node {
+                    width: Val::Percent(100.0),
+                    justify_content: JustifyContent::FlexEnd,
+                    ..default()
+                },
+                children![
+                    (
+                        Button,
+                        button_node.clone(),
+                        BackgroundColor(NORMAL_BUTTON),
+                        BattleButtonAction::Undo,
+                        UndoButton,
+                        Visibility::Hidden,
+                        children![(Text::new("undo"), text_font.clone(), TextColor(TEXT))]
+                    ),
+                    (
+                        Button,
+                        button_node,
+                        BackgroundColor(NORMAL_BUTTON),
+                        BattleButtonAction::EndTurn,
+                        children![(Text::new("end turn"), text_font, TextColor(TEXT))]
+                    ),
+                ]
+            ),
+        ],
     ));
 }
 
-fn handle_battle_ui(mut commands: Commands) {}
+fn sync_hand(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    battle: Res<Battle>,
+    cards_handle: Res<CardLibraryHandle>,
+    cards: Res<Assets<CardLibrary>>,
+    hand_query: Query<Entity, With<HandCard>>,
+) {
+    for card_entity in &hand_query {
+        commands.entity(card_entity).despawn();
+    }
+    let Some(cards) = cards.get(cards_handle.0.id()) else {
+        return;
+    };
+    let hand = battle.deck.cards(CardPlace::Hand);
+    for (i, owned) in hand.iter().enumerate() {
+        let Some(card) = cards.resolve(owned) else {
+            log::warn!(card = owned.id.0.as_str(); "Missing asset while spawning {}", owned.id.0);
+            continue;
+        };
+        let position = vec2(
+            (i as f32 - (hand.len() as f32 - 1.) / 2.) * HAND_SPACING,
+            HAND_Y,
+        );
+        spawn_card_node(
+            &mut commands,
+            &asset_server,
+            &card,
+            InteractiveNode::homed(position),
+            position,
+        )
+        .insert((OnBattleScreen, HandCard(i)));
+    }
+}
+
+fn sync_stats_text(
+    battle: Res<Battle>,
+    mut player_text_query: Query<&mut Text, (With<PlayerStatsText>, Without<EnemyStatsText>)>,
+    mut enemy_text_query: Query<&mut Text, (With<EnemyStatsText>, Without<PlayerStatsText>)>,
+) {
+    let resources = [
+        battle.kind.resource(),
+        stats::Resource::Stamina,
+        stats::Resource::Mana,
+    ];
+    for mut text in &mut player_text_query {
+        text.0 = format!(
+            "Turn {}  {}",
+            battle.turn,
+            describe(&battle.player.stats, &resources)
+        );
+    }
+    for mut text in &mut enemy_text_query {
+        text.0 = battle
+            .enemies
+            .iter()
+            .map(|enemy| {
+                format!(
+                    "{}  {}",
+                    enemy.name,
+                    describe(&enemy.stats, &[battle.kind.resource()])
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+    }
+}
+
+/// Offer to take back the last card play, if the setting allows it
+fn sync_undo_button(
+    battle: Res<Battle>,
+    settings: Res<Settings>,
+    mut undo_button_query: Query<&mut Visibility, With<UndoButton>>,
+) {
+    for mut visibility in &mut undo_button_query {
+        *visibility = if settings.undo && battle.undo.is_some() {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+}
+
+/// Move the target off a defeated enemy to the first one still standing
+fn sync_target(battle: Res<Battle>, mut targeted: ResMut<TargetedEnemy>) {
+    let defeated = |i: usize| battle.enemies[i].is_defeated(battle.kind);
+    if targeted.0 < battle.enemies.len()
+        && defeated(targeted.0)
+        && let Some(living) = (0..battle.enemies.len()).find(|&i| !defeated(i))
+    {
+        targeted.0 = living;
+    }
+}
+
+fn handle_card_drops(
+    mut node_interaction_events: EventReader<NodeInteraction>,
+    hand_query: Query<(&HandCard, &InteractiveNode)>,
+    enemy_query: Query<&EnemyNode>,
+    mut battle_actions: EventWriter<BattleAction>,
+) {
+    for interaction in node_interaction_events.read() {
+        if interaction.interaction_type != NodeInteractionType::LeftDrop {
+            continue;
+        }
+        if let Ok((HandCard(card), interactive_node)) = hand_query.get(interaction.entity)
+            && let Some(EnemyNode(target)) = interactive_node
+                .drop_zone()
+                .and_then(|zone| enemy_query.get(zone).ok())
+        {
+            battle_actions.write(BattleAction::PlayCard {
+                card: *card,
+                target: *target,
+            });
+        }
+    }
+}
+
+fn handle_battle_buttons(
+    interaction_query: Query<
+        (&Interaction, &BattleButtonAction),
+        (Changed<Interaction>, With<Button>),
+    >,
+    mut battle_actions: EventWriter<BattleAction>,
+) {
+    for (interaction, battle_button_action) in &interaction_query {
+        if *interaction == Interaction::Pressed {
+            match battle_button_action {
+                BattleButtonAction::Undo => {
+                    battle_actions.write(BattleAction::Undo);
+                }
+                BattleButtonAction::EndTurn => {
+                    battle_actions.write(BattleAction::EndTurn);
+                }
+            }
+        }
+    }
+}
+
+fn handle_battle_input(
+    mut input_actions: EventReader<InputAction>,
+    battle: Res<Battle>,
+    mut targeted: ResMut<TargetedEnemy>,
+    focused_query: Query<&HandCard, With<Focused>>,
+    mut battle_actions: EventWriter<BattleAction>,
+) {
+    for action in input_actions.read() {
+        match action {
+            InputAction::Target => {
+                let count = battle.enemies.len();
+                if let Some(next) = (1..=count)
+                    .map(|step| (targeted.0 + step) % count)
+                    .find(|&i| !battle.enemies[i].is_defeated(battle.kind))
+                {
+                    targeted.0 = next;
+                }
+            }
+            InputAction::Play => {
+                if let Ok(HandCard(card)) = focused_query.single() {
+                    battle_actions.write(BattleAction::PlayCard {
+                        card: *card,
+                        target: targeted.0,
+                    });
+                }
+            }
+            InputAction::EndTurn => {
+                battle_actions.write(BattleAction::EndTurn);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Mark the enemy a focused card would be played against
+fn highlight_target(
+    mut gizmos: Gizmos,
+    targeted: Res<TargetedEnemy>,
+    enemy_query: Query<(&EnemyNode, &GlobalTransform)>,
+    focused_query: Query<(), (With<HandCard>, With<Focused>)>,
+) {
+    if focused_query.is_empty() {
+        return;
+    }
+    for (EnemyNode(i), transform) in &enemy_query {
+        if *i == targeted.0 {
+            gizmos.rect_2d(
+                Isometry2d::from_translation(transform.translation().truncate()),
+                ENEMY_SIZE,
+                TARGET,
+            );
+        }
+    }
+}
 
-fn teardown_battle_ui(mut commands: Commands) {}
+fn resolve_battle_actions(
+    mut battle_actions: EventReader<BattleAction>,
+    mut battle: ResMut<Battle>,
+    run: Res<RunState>,
+    cards_handle: Res<CardLibraryHandle>,
+    cards: Res<Assets<CardLibrary>>,
+    settings: Res<Settings>,
+    mut recording: Option<ResMut<BattleRecording>>,
+    mut playback: Option<ResMut<ReplayPlayback>>,
+    mut battle_state: ResMut<NextState<BattleState>>,
+) {
+    let Some(cards) = cards.get(cards_handle.0.id()) else {
+        return;
+    };
+    for action in battle_actions.read() {
+        // A replay took back whatever it recorded, whatever the setting now
+        if *action == BattleAction::Undo && !settings.undo && playback.is_none() {
+            continue;
+        }
+        match action {
+            BattleAction::PlayCard { card, target } => {
+                if let Some(played) = battle.play_card(*card, *target, cards) {
+                    log::info!(card = played.0.as_str(), target; "Played {} against {}", played.0, target);
+                    if playback.is_none() {
+                        telemetry::record(&run, TelemetryEvent::CardPlayed { card: played });
+                    }
+                }
+            }
+            BattleAction::Undo => {
+                if let Some(undone) = battle.undo() {
+                    log::info!(card = undone.0.as_str(); "Took back {}", undone.0);
+                    if playback.is_none() {
+                        telemetry::record(&run, TelemetryEvent::CardUndone { card: undone });
+                    }
+                }
+            }
+            BattleAction::EndTurn => {
+                battle_state.set(BattleState::Cpu);
+            }
+        }
+        if let Some(recording) = recording.as_mut() {
+            recording.push(action, replay::state_hash(&battle));
+        }
+        if let Some(playback) = playback.as_mut() {
+            playback.verify(&battle);
+        }
+        if battle.outcome().is_some() {
+            battle_state.set(BattleState::End);
+            break;
+        }
+    }
+}
+
+fn begin_turn(mut battle: ResMut<Battle>, mut battle_state: ResMut<NextState<BattleState>>) {
+    battle.start_turn();
+    battle_state.set(BattleState::Human);
+}
+
+fn cpu_turn(
+    mut battle: ResMut<Battle>,
+    cards_handle: Res<CardLibraryHandle>,
+    cards: Res<Assets<CardLibrary>>,
+    mut battle_state: ResMut<NextState<BattleState>>,
+) {
+    if let Some(cards) = cards.get(cards_handle.0.id()) {
+        battle.enemy_turn(cards);
+    }
+    battle_state.set(match battle.outcome() {
+        Some(_) => BattleState::End,
+        None => BattleState::World,
+    });
+}
+
+fn world_turn(mut battle: ResMut<Battle>, mut battle_state: ResMut<NextState<BattleState>>) {
+    battle.end_turn();
+    battle_state.set(BattleState::Begin);
+}
+
+fn end_battle(
+    mut battle: ResMut<Battle>,
+    mut run: ResMut<RunState>,
+    mut app_state: ResMut<NextState<AppState>>,
+) {
+    if battle.outcome() == Some(BattleOutcome::Victory) {
+        battle.trigger(Trigger::Victory);
+    }
+    run.stats = battle.player.stats.clone();
+    run.flags.extend(battle.flags.iter().cloned());
+    for card in &battle.spent {
+        if let Some(index) = run.deck.iter().position(|owned| owned.id == *card) {
+            run.deck.remove(index);
+        }
+    }
+    run.inventory.retain(|id| !battle.stolen.contains(id));
+    for (card, times) in &battle.played {
+        *run.record.cards_played.entry(card.clone()).or_default() += times;
+    }
+    run.record.damage_dealt += battle.damage_dealt;
+    let won = battle.outcome() == Some(BattleOutcome::Victory);
+    telemetry::record(
+        &run,
+        TelemetryEvent::EncounterEnded {
+            enemies: battle
+                .enemies
+                .iter()
+                .filter_map(|enemy| enemy.actor.clone())
+                .collect(),
+            kind: battle.kind,
+            won,
+            turns: battle.turn,
+            damage_taken: battle.damage_taken,
+            damage_dealt: battle.damage_dealt,
+        },
+    );
+    match battle.outcome() {
+        Some(BattleOutcome::Defeat) => {
+            log::info!(turns = battle.turn; "Defeated after {} turns", battle.turn);
+            let standing = battle
+                .enemies
+                .iter()
+                .filter(|enemy| !enemy.is_defeated(battle.kind))
+                .map(|enemy| enemy.name.clone())
+                .collect::<Vec<_>>();
+            run.record.defeated_by = Some(standing.join(", "));
+            telemetry::record(
+                &run,
+                TelemetryEvent::Death {
+                    cause: standing.join(", "),
+                },
+            );
+            telemetry::record(&run, TelemetryEvent::RunEnded { victory: false });
+            // A lost run can't be continued
+            save::delete();
+            app_state.set(AppState::GameOver);
+        }
+        _ => {
+            log::info!(turns = battle.turn; "Victorious after {} turns", battle.turn);
+            run.record.encounters_won += 1;
+            run.silver += battle.enemies.iter().map(|enemy| enemy.silver).sum::<u32>();
+            app_state.set(AppState::Reward);
+        }
+    }
+}
+
+fn teardown_battle(
+    mut commands: Commands,
+    battle_items_query: Query<Entity, Or<(With<OnBattleScreen>, With<SpawnedByScript>)>>,
+) {
+    for battle_entity in &battle_items_query {
+        commands.entity(battle_entity).despawn();
+    }
+    commands.remove_resource::<Battle>();
+    commands.remove_resource::<TargetedEnemy>();
+}
 
 #[derive(Default)]
 pub struct BattlePlugin;
 
 impl Plugin for BattlePlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(OnEnter(AppState::Battle), setup_battle_ui)
-            .add_systems(Update, handle_battle_ui)
-            .add_systems(OnExit(AppState::Battle), teardown_battle_ui);
+        app.add_event::<BattleAction>()
+            .add_systems(
+                OnEnter(AppState::Battle),
+                (replay::begin_recording, setup_battle, setup_battle_ui).chain(),
+            )
+            .add_systems(
+                OnEnter(BattleState::Begin),
+                begin_turn.run_if(resource_exists::<Battle>),
+            )
+            .add_systems(
+                OnEnter(BattleState::Cpu),
+                cpu_turn.run_if(resource_exists::<Battle>),
+            )
+            .add_systems(
+                OnEnter(BattleState::World),
+                world_turn.run_if(resource_exists::<Battle>),
+            )
+            .add_systems(
+                OnEnter(BattleState::End),
+                end_battle
+                    .run_if(resource_exists::<Battle>.and(not(resource_exists::<ReplayPlayback>))),
+            )
+            .add_systems(
+                Update,
+                (
+                    (
+                        (
+                            handle_card_drops,
+                            handle_battle_buttons,
+                            handle_battle_input,
+                        )
+                            .run_if(not(resource_exists::<ReplayPlayback>)),
+                        resolve_battle_actions,
+                    )
+                        .chain()
+                        .run_if(in_state(BattleState::Human).and(resource_exists::<Battle>)),
+                    (sync_hand, sync_stats_text, sync_undo_button, sync_target)
+                        .run_if(resource_exists_and_changed::<Battle>),
+                    highlight_target.run_if(resource_exists::<TargetedEnemy>),
+                )
+                    .run_if(in_state(AppState::Battle)),
+            )
+            .add_systems(OnExit(AppState::Battle), teardown_battle);
     }
 }
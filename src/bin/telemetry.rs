@@ -0,0 +1,188 @@
+//! Aggregate run telemetry into card and encounter tables for balancing.
+//!
+//! Reads every `.jsonl` file in the directory given, or the game's own
+//! telemetry directory by default.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use pipedream::asset::card::CardId;
+use pipedream::run::save::data_path;
+use pipedream::run::telemetry::{TELEMETRY_DIR, TelemetryEvent, TelemetryRecord};
+
+#[derive(Default)]
+struct CardStats {
+    offered: u32,
+    picked: u32,
+    played: u32,
+    runs: u32,
+    wins: u32,
+}
+
+#[derive(Default)]
+struct EncounterStats {
+    fights: u32,
+    deaths: u32,
+    damage_taken: i64,
+}
+
+#[derive(Default)]
+struct Tables {
+    runs: u32,
+    wins: u32,
+    cards: BTreeMap<CardId, CardStats>,
+    encounters: BTreeMap<String, EncounterStats>,
+}
+
+impl Tables {
+    fn add_run(&mut self, records: &[TelemetryRecord]) {
+        let victory = records.iter().find_map(|record| match record.event {
+            TelemetryEvent::RunEnded { victory } => Some(victory),
+            _ => None,
+        });
+        let mut picked = BTreeSet::new();
+        for record in records {
+            match &record.event {
+                TelemetryEvent::CardsOffered { cards } => {
+                    for card in cards {
+                        self.cards.entry(card.clone()).or_default().offered += 1;
+                    }
+                }
+                TelemetryEvent::CardPicked { card: Some(card) } => {
+                    self.cards.entry(card.clone()).or_default().picked += 1;
+                    picked.insert(card.clone());
+                }
+                TelemetryEvent::CardPlayed { card } => {
+                    self.cards.entry(card.clone()).or_default().played += 1;
+                }
+                TelemetryEvent::CardUndone { card } => {
+                    let stats = self.cards.entry(card.clone()).or_default();
+                    stats.played = stats.played.saturating_sub(1);
+                }
+                TelemetryEvent::EncounterEnded {
+                    enemies,
+                    won,
+                    damage_taken,
+                    ..
+                } => {
+                    let names = enemies
+                        .iter()
+                        .map(|enemy| enemy.0.as_str())
+                        .collect::<Vec<_>>();
+                    let encounter = self.encounters.entry(names.join("+")).or_default();
+                    encounter.fights += 1;
+                    encounter.damage_taken += damage_taken;
+                    if !won {
+                        encounter.deaths += 1;
+                    }
+                }
+                _ => {}
+            }
+        }
+        // Unfinished runs count towards pick rates but not win rates
+        let Some(victory) = victory else {
+            return;
+        };
+        self.runs += 1;
+        self.wins += victory as u32;
+        for card in picked {
+            let stats = self.cards.entry(card).or_default();
+            stats.runs += 1;
+            stats.wins += victory as u32;
+        }
+    }
+
+    fn print(&self) {
+        println!(
+            "{} finished runs, {:.1}% won\n",
+            self.runs,
+            rate(self.wins, self.runs)
+        );
+        println!(
+            "{:<24} {:>8} {:>8} {:>8} {:>8} {:>8}",
+            "card", "offered", "picked", "pick %", "played", "win %"
+        );
+        for (id, card) in &self.cards {
+            println!(
+                "{:<24} {:>8} {:>8} {:>8.1} {:>8} {:>8.1}",
+                id.0,
+                card.offered,
+                card.picked,
+                rate(card.picked, card.offered),
+                card.played,
+                rate(card.wins, card.runs)
+            );
+        }
+        println!(
+            "\n{:<32} {:>8} {:>8} {:>10} {:>12}",
+            "encounter", "fights", "deaths", "lethality", "avg damage"
+        );
+        let mut encounters = self.encounters.iter().collect::<Vec<_>>();
+        encounters
+            .sort_by(|a, b| rate(b.1.deaths, b.1.fights).total_cmp(&rate(a.1.deaths, a.1.fights)));
+        for (name, encounter) in encounters {
+            println!(
+                "{:<32} {:>8} {:>8} {:>9.1}% {:>12.1}",
+                name,
+                encounter.fights,
+                encounter.deaths,
+                rate(encounter.deaths, encounter.fights),
+                encounter.damage_taken as f64 / encounter.fights.max(1) as f64
+            );
+        }
+    }
+}
+
+fn rate(count: u32, total: u32) -> f64 {
+    if total == 0 {
+        0.
+    } else {
+        count as f64 * 100. / total as f64
+    }
+}
+
+fn read_run(path: &Path) -> Vec<TelemetryRecord> {
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(err) => {
+            eprintln!("Failed to read {}: {}", path.display(), err);
+            return Vec::new();
+        }
+    };
+    contents
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| {
+            serde_json::from_str(line)
+                .inspect_err(|err| eprintln!("Skipping line of {}: {}", path.display(), err))
+                .ok()
+        })
+        .collect()
+}
+
+fn main() {
+    let Some(dir) = std::env::args()
+        .nth(1)
+        .map(PathBuf::from)
+        .or_else(|| data_path(TELEMETRY_DIR))
+    else {
+        eprintln!("No telemetry directory given and no data directory found");
+        std::process::exit(1);
+    };
+    let entries = match fs::read_dir(&dir) {
+        Ok(entries) => entries,
+        Err(err) => {
+            eprintln!("Failed to read {}: {}", dir.display(), err);
+            std::process::exit(1);
+        }
+    };
+    let mut tables = Tables::default();
+    for path in entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "jsonl"))
+    {
+        tables.add_run(&read_run(&path));
+    }
+    tables.print();
+}
@@ -1,9 +1,12 @@
 use bevy::prelude::*;
 
+use crate::campaign::map::NodeId;
+
 #[derive(Component)]
 pub struct Location {
+    pub node: NodeId,
     pub title: String,
     pub details: Vec<String>,
-    pub image: Image,
+    pub image: Handle<Image>,
     pub selectable: bool,
 }
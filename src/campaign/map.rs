@@ -0,0 +1,398 @@
+use std::collections::BTreeSet;
+
+use bevy::prelude::*;
+use rand::{Rng, SeedableRng, rngs::StdRng, seq::IndexedRandom};
+use serde::{Deserialize, Serialize};
+
+use crate::asset::actor::ActorId;
+use crate::asset::dialogue::DialogueId;
+use crate::asset::realm::{Realm, RealmId};
+use crate::state::AppState;
+
+const LAYERS: usize = 8;
+const MIN_LANES: usize = 2;
+const MAX_LANES: usize = 4;
+const ELITE_MIN_LAYER: usize = 3;
+const EXTRA_EDGE_CHANCE: f64 = 0.35;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct NodeId(pub usize);
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NodeType {
+    Combat,
+    Negotiation,
+    Event,
+    Shop,
+    Rest,
+    Elite,
+    Wellspring,
+}
+impl NodeType {
+    pub fn title(&self) -> &'static str {
+        match self {
+            Self::Combat => "Combat",
+            Self::Negotiation => "Negotiation",
+            Self::Event => "Event",
+            Self::Shop => "Shop",
+            Self::Rest => "Rest",
+            Self::Elite => "Elite",
+            Self::Wellspring => "Wellspring",
+        }
+    }
+
+    pub fn image_path(&self) -> &'static str {
+        match self {
+            Self::Combat => "map/combat.png",
+            Self::Negotiation => "map/negotiation.png",
+            Self::Event => "map/event.png",
+            Self::Shop => "map/shop.png",
+            Self::Rest => "map/rest.png",
+            Self::Elite => "map/elite.png",
+            Self::Wellspring => "map/wellspring.png",
+        }
+    }
+
+    /// The state entered when this node is chosen, or None if it resolves on the map
+    pub fn app_state(&self) -> Option<AppState> {
+        match self {
+            Self::Combat | Self::Negotiation | Self::Elite => Some(AppState::Battle),
+            Self::Rest => Some(AppState::Rest),
+            Self::Wellspring => Some(AppState::Wellspring),
+            Self::Shop => Some(AppState::Shop),
+            Self::Event => None,
+        }
+    }
+
+    fn weight(&self, layer: usize) -> u32 {
+        match self {
+            Self::Combat => 45,
+            Self::Negotiation => 20,
+            Self::Event => 20,
+            Self::Shop => 5,
+            Self::Rest => 10,
+            Self::Elite if layer >= ELITE_MIN_LAYER => 8,
+            Self::Elite | Self::Wellspring => 0,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct MapNode {
+    pub node_type: NodeType,
+    pub title: String,
+    pub layer: usize,
+    pub lane: usize,
+    pub edges: Vec<NodeId>,
+    pub encounter: Option<ActorId>,
+    #[serde(default)]
+    pub dialogue: Option<DialogueId>,
+    /// Lua script run when the node is chosen
+    #[serde(default)]
+    pub event: Option<String>,
+    pub image: Option<String>,
+    pub requires: Vec<String>,
+}
+
+impl MapNode {
+    pub fn image_path(&self) -> &str {
+        self.image
+            .as_deref()
+            .unwrap_or_else(|| self.node_type.image_path())
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct CampaignMap {
+    pub realm: RealmId,
+    pub background: Option<String>,
+    pub nodes: Vec<MapNode>,
+    pub current: Option<NodeId>,
+    pub selected: Option<NodeId>,
+}
+
+impl CampaignMap {
+    /// Build the map for a realm, from its authored nodes if it declares any
+    pub fn from_realm(id: &RealmId, realm: &Realm, encounters: &[ActorId], seed: u64) -> Self {
+        let mut map = if realm.nodes.is_empty() {
+            Self::generate(id, encounters, seed)
+        } else {
+            let keys = realm.nodes.keys().collect::<Vec<_>>();
+            let mut nodes = realm
+                .nodes
+                .values()
+                .map(|node| MapNode {
+                    node_type: node.node_type,
+                    title: node.name.clone(),
+                    layer: node.layer,
+                    lane: node.lane,
+                    edges: Vec::new(),
+                    encounter: node.encounter.clone(),
+                    dialogue: node.dialogue.clone(),
+                    event: node.event.clone(),
+                    image: node.image.clone(),
+                    requires: node.requires.clone(),
+                })
+                .collect::<Vec<_>>();
+            for (from, to) in &realm.edges {
+                match (
+                    keys.iter().position(|&key| key == from),
+                    keys.iter().position(|&key| key == to),
+                ) {
+                    (Some(from), Some(to)) => nodes[from].edges.push(NodeId(to)),
+                    _ => log::warn!("Missing node for edge {} -> {} in {}", from, to, id.0),
+                }
+            }
+            Self {
+                realm: id.clone(),
+                nodes,
+                ..default()
+            }
+        };
+        map.background = Some(realm.background.clone());
+        let mut rng = StdRng::seed_from_u64(seed);
+        for node in map.nodes.iter_mut() {
+            if node.node_type == NodeType::Wellspring && node.encounter.is_none() {
+                node.encounter = realm.boss.clone();
+            }
+            if node.node_type == NodeType::Event
+                && node.dialogue.is_none()
+                && node.event.is_none()
+                && node.encounter.is_none()
+            {
+                node.event = realm.events.choose(&mut rng).cloned();
+            }
+        }
+        map
+    }
+
+    /// Generate a layered map where every node is reachable from the first layer
+    /// and every path ends at a single Wellspring
+    pub fn generate(realm: &RealmId, encounters: &[ActorId], seed: u64) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut layers: Vec<Vec<NodeType>> = Vec::with_capacity(LAYERS);
+        for layer in 0..LAYERS {
+            let node_types = if layer == 0 {
+                vec![NodeType::Combat; rng.random_range(MIN_LANES..=MAX_LANES - 1)]
+            } else if layer == LAYERS - 1 {
+                vec![NodeType::Wellspring]
+            } else if layer == LAYERS - 2 {
+                vec![NodeType::Rest; rng.random_range(MIN_LANES..=MAX_LANES)]
+            } else {
+                let options = [
+                    NodeType::Combat,
+                    NodeType::Negotiation,
+                    NodeType::Event,
+                    NodeType::Shop,
+                    NodeType::Rest,
+                    NodeType::Elite,
+                ];
+                (0..rng.random_range(MIN_LANES..=MAX_LANES))
+                    .map(|_| {
+                        *options
+                            .choose_weighted(&mut rng, |node_type| node_type.weight(layer))
+                            .unwrap_or(&NodeType::Combat)
+                    })
+                    .collect()
+            };
+            layers.push(node_types);
+        }
+
+        let mut nodes = Vec::new();
+        let mut layer_ids: Vec<Vec<NodeId>> = Vec::with_capacity(LAYERS);
+        for (layer, node_types) in layers.iter().enumerate() {
+            let mut ids = Vec::with_capacity(node_types.len());
+            for (lane, node_type) in node_types.iter().enumerate() {
+                ids.push(NodeId(nodes.len()));
+                nodes.push(MapNode {
+                    node_type: *node_type,
+                    title: node_type.title().to_string(),
+                    layer,
+                    lane,
+                    edges: Vec::new(),
+                    encounter: match node_type {
+                        NodeType::Combat | NodeType::Negotiation | NodeType::Elite => {
+                            encounters.choose(&mut rng).cloned()
+                        }
+                        _ => None,
+                    },
+                    dialogue: None,
+                    event: None,
+                    image: None,
+                    requires: Vec::new(),
+                });
+            }
+            layer_ids.push(ids);
+        }
+
+        for pair in layer_ids.windows(2) {
+            let (from, to) = (&pair[0], &pair[1]);
+            let mut has_incoming = vec![false; to.len()];
+            for (i, &NodeId(source)) in from.iter().enumerate() {
+                let target = proportional_index(i, from.len(), to.len());
+                nodes[source].edges.push(to[target]);
+                has_incoming[target] = true;
+                if target + 1 < to.len() && rng.random_bool(EXTRA_EDGE_CHANCE) {
+                    nodes[source].edges.push(to[target + 1]);
+                    has_incoming[target + 1] = true;
+                }
+            }
+            for (j, incoming) in has_incoming.iter().enumerate() {
+                if !incoming {
+                    let NodeId(source) = from[proportional_index(j, to.len(), from.len())];
+                    nodes[source].edges.push(to[j]);
+                }
+            }
+        }
+
+        Self {
+            realm: realm.clone(),
+            nodes,
+            ..default()
+        }
+    }
+
+    pub fn node(&self, id: NodeId) -> Option<&MapNode> {
+        self.nodes.get(id.0)
+    }
+
+    pub fn lanes(&self, layer: usize) -> usize {
+        self.nodes.iter().filter(|node| node.layer == layer).count()
+    }
+
+    pub fn is_unlocked(&self, id: NodeId, flags: &BTreeSet<String>) -> bool {
+        self.node(id)
+            .is_some_and(|node| node.requires.iter().all(|flag| flags.contains(flag)))
+    }
+
+    /// Nodes the player may choose next
+    pub fn reachable(&self, flags: &BTreeSet<String>) -> Vec<NodeId> {
+        let candidates = match self.current.and_then(|current| self.node(current)) {
+            Some(node) => node.edges.clone(),
+            None => self
+                .nodes
+                .iter()
+                .enumerate()
+                .filter(|(_, node)| node.layer == 0)
+                .map(|(i, _)| NodeId(i))
+                .collect(),
+        };
+        candidates
+            .into_iter()
+            .filter(|&id| self.is_unlocked(id, flags))
+            .collect()
+    }
+
+    /// Move the player onto the selected node, if any
+    pub fn advance(&mut self) -> Option<NodeId> {
+        if let Some(selected) = self.selected.take() {
+            self.current = Some(selected);
+        }
+        self.current
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.current
+            .and_then(|current| self.node(current))
+            .is_some_and(|node| node.edges.is_empty())
+    }
+}
+
+fn proportional_index(index: usize, from_len: usize, to_len: usize) -> usize {
+    if from_len <= 1 {
+        (to_len - 1) / 2
+    } else {
+        (index * (to_len - 1) + (from_len - 1) / 2) / (from_len - 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SEEDS: std::ops::Range<u64> = 0..500;
+
+    fn generate(seed: u64) -> CampaignMap {
+        let encounters = [ActorId("frost_wolf".to_string())];
+        CampaignMap::generate(&RealmId("niflheim".to_string()), &encounters, seed)
+    }
+
+    #[test]
+    fn every_node_is_reachable_from_the_first_layer() {
+        for seed in SEEDS {
+            let map = generate(seed);
+            let mut seen = map.reachable(&BTreeSet::new());
+            assert!(!seen.is_empty(), "seed {} has no first layer", seed);
+            let mut frontier = seen.clone();
+            while let Some(id) = frontier.pop() {
+                for &next in &map.node(id).unwrap().edges {
+                    if !seen.contains(&next) {
+                        seen.push(next);
+                        frontier.push(next);
+                    }
+                }
+            }
+            assert_eq!(seen.len(), map.nodes.len(), "seed {}", seed);
+        }
+    }
+
+    #[test]
+    fn every_path_ends_at_a_single_wellspring() {
+        for seed in SEEDS {
+            let map = generate(seed);
+            let wellsprings = (map.nodes.iter())
+                .filter(|node| node.node_type == NodeType::Wellspring)
+                .collect::<Vec<_>>();
+            assert_eq!(wellsprings.len(), 1, "seed {}", seed);
+            assert!(wellsprings[0].edges.is_empty(), "seed {}", seed);
+            assert_eq!(wellsprings[0].layer, LAYERS - 1, "seed {}", seed);
+            for node in map.nodes.iter() {
+                if node.node_type == NodeType::Wellspring {
+                    continue;
+                }
+                // Edges only lead one layer on, so every path climbs to the last
+                assert!(!node.edges.is_empty(), "seed {} strands {:?}", seed, node);
+                for &next in &node.edges {
+                    assert_eq!(map.node(next).unwrap().layer, node.layer + 1);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn layers_respect_lane_limits() {
+        for seed in SEEDS {
+            let map = generate(seed);
+            for layer in 0..LAYERS - 1 {
+                let lanes = map.lanes(layer);
+                assert!((MIN_LANES..=MAX_LANES).contains(&lanes), "seed {}", seed);
+            }
+            for node in map.nodes.iter() {
+                if node.node_type == NodeType::Elite {
+                    assert!(node.layer >= ELITE_MIN_LAYER, "seed {}", seed);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn same_seed_generates_the_same_map() {
+        for seed in 0..20 {
+            assert_eq!(
+                serde_json::to_value(generate(seed)).unwrap(),
+                serde_json::to_value(generate(seed)).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn proportional_index_stays_in_bounds() {
+        for from_len in 1..=MAX_LANES {
+            for to_len in 1..=MAX_LANES {
+                for index in 0..from_len {
+                    assert!(proportional_index(index, from_len, to_len) < to_len);
+                }
+            }
+        }
+    }
+}
@@ -1,20 +1,390 @@
 pub mod location;
+pub mod map;
 
 use bevy::prelude::*;
 use bevy_scriptum::prelude::*;
 use bevy_scriptum::runtimes::lua::prelude::*;
 
+use crate::asset::actor::{ActorLibrary, ActorLibraryHandle, ActorType};
+use crate::asset::card::{CardLibrary, CardLibraryHandle};
+use crate::asset::dialogue::{DialogueLibrary, DialogueLibraryHandle};
+use crate::asset::realm::{RealmId, RealmLibrary, RealmLibraryHandle};
+use crate::campaign::location::Location;
+use crate::campaign::map::{CampaignMap, NodeId, NodeType};
+use crate::cards::InteractiveNode;
+use crate::cards::event::{NodeInteraction, NodeInteractionType};
+use crate::cards::script::SpawnedByScript;
+use crate::dialogue::ActiveDialogue;
+use crate::event::ActiveEvent;
+use crate::menu::resource::Language;
+use crate::menu::{BACKDROP, NORMAL_BUTTON, TEXT};
+use crate::run::RunState;
+use crate::run::rng::RngStream;
+use crate::run::save;
+use crate::run::telemetry::{self, TelemetryEvent};
 use crate::state::AppState;
+use crate::zindex::{Z_BACKGROUND, Z_LOCATION};
+use crate::{HEIGHT, WIDTH};
 
-fn setup_campaign(mut commands: Commands, asset_server: Res<AssetServer>) {
+const LOCATION_SIZE: f32 = 24.;
+const LANE_SPACING: f32 = 64.;
+
+const SELECTABLE: Color = Color::srgb(1.0, 1.0, 1.0);
+const VISITED: Color = Color::srgb(0.45, 0.45, 0.45);
+const UNREACHABLE: Color = Color::srgba(0.6, 0.6, 0.6, 0.6);
+const EDGE: Color = Color::srgba(0.9, 0.9, 0.9, 0.5);
+
+#[derive(Component)]
+struct RealmBackground;
+
+#[derive(Component)]
+struct LockedRealmPanel;
+
+#[derive(Component)]
+struct EndRunButton;
+
+fn build_map(
+    realm_id: &RealmId,
+    realms: Option<&RealmLibrary>,
+    run: &RunState,
+    seed: u64,
+) -> CampaignMap {
+    match realms.and_then(|realms| realms.0.get(realm_id)) {
+        Some(realm) => CampaignMap::from_realm(realm_id, realm, &realm.encounters(run), seed),
+        None => {
+            log::warn!(
+                realm = realm_id.0.as_str();
+                "Missing realm {}, generating without encounters",
+                realm_id.0
+            );
+            CampaignMap::generate(realm_id, &[], seed)
+        }
+    }
+}
+
+fn location_position(map: &CampaignMap, node: NodeId) -> Vec2 {
+    let Some(map_node) = map.node(node) else {
+        return Vec2::ZERO;
+    };
+    let layers = map.nodes.iter().map(|n| n.layer).max().unwrap_or(0);
+    let lanes = map.lanes(map_node.layer);
+    let x = if layers == 0 {
+        0.
+    } else {
+        -WIDTH / 2. + 48. + map_node.layer as f32 * (WIDTH - 96.) / layers as f32
+    };
+    let y = (map_node.lane as f32 - (lanes as f32 - 1.) / 2.) * LANE_SPACING;
+    vec2(x, y.clamp(-HEIGHT / 2. + 24., HEIGHT / 2. - 24.))
+}
+
+fn setup_campaign(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    realms_handle: Res<RealmLibraryHandle>,
+    realms: Res<Assets<RealmLibrary>>,
+    mut run: ResMut<RunState>,
+) {
     commands.spawn(Script::<LuaScript>::new(
         asset_server.load("scripts/spawn_campaign.lua"),
     ));
+
+    if run.map.nodes.is_empty() {
+        let seed = run.rng.next_seed(RngStream::Map);
+        let realm = run.map.realm.clone();
+        run.map = build_map(&realm, realms.get(realms_handle.0.id()), &run, seed);
+    } else {
+        // Returning from an encounter moves the player onto the node they chose
+        run.map.advance();
+        save::save(&run);
+    }
 }
 
-fn handle_campaign(mut commands: Commands) {}
+/// Offer to end the run when the map is complete but the next realm is locked
+fn spawn_locked_realm(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    language: &Language,
+    name: &str,
+) {
+    let text_font = TextFont {
+        font_size: 16.0,
+        font: asset_server.load(language.font_path()),
+        ..default()
+    };
+    commands.spawn((
+        Node {
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            align_items: AlignItems::Center,
+            justify_content: JustifyContent::Center,
+            ..default()
+        },
+        LockedRealmPanel,
+        children![(
+            Node {
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                padding: UiRect::all(Val::Px(12.0)),
+                ..default()
+            },
+            BackgroundColor(BACKDROP),
+            children![
+                (
+                    Text::new(format!("The way to {} is barred.", name)),
+                    text_font.clone(),
+                    TextColor(TEXT),
+                ),
+                (
+                    Button,
+                    Node {
+                        margin: UiRect::top(Val::Px(6.0)),
+                        padding: UiRect::horizontal(Val::Px(8.0)),
+                        ..default()
+                    },
+                    BackgroundColor(NORMAL_BUTTON),
+                    EndRunButton,
+                    children![(Text::new("end the run"), text_font, TextColor(TEXT))],
+                ),
+            ],
+        )],
+    ));
+}
 
-fn teardown_campaign(mut commands: Commands) {}
+/// Give up a run stranded before a locked realm, counting it as a loss
+fn handle_end_run(
+    interaction_query: Query<&Interaction, (Changed<Interaction>, With<EndRunButton>)>,
+    run: Res<RunState>,
+    mut app_state: ResMut<NextState<AppState>>,
+) {
+    if interaction_query
+        .iter()
+        .any(|interaction| *interaction == Interaction::Pressed)
+    {
+        log::info!(realm = run.map.realm.0.as_str(); "Ended the run in {}", run.map.realm.0);
+        telemetry::record(&run, TelemetryEvent::RunEnded { victory: false });
+        save::delete();
+        save::delete_checkpoint();
+        app_state.set(AppState::GameOver);
+    }
+}
+
+fn advance_realm(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    language: Res<Language>,
+    locked_query: Query<(), With<LockedRealmPanel>>,
+    mut run: ResMut<RunState>,
+    realms_handle: Res<RealmLibraryHandle>,
+    realms: Res<Assets<RealmLibrary>>,
+    mut app_state: ResMut<NextState<AppState>>,
+) {
+    if !run.map.is_complete() {
+        return;
+    }
+    let realms = realms.get(realms_handle.0.id());
+    let Some(realm) = realms.and_then(|realms| realms.0.get(&run.map.realm)) else {
+        return;
+    };
+    // Completing the last realm wins the run
+    let Some(next) = &realm.next else {
+        log::info!(realm = run.map.realm.0.as_str(); "Completed the run in {}", run.map.realm.0);
+        run.record.victory = true;
+        telemetry::record(&run, TelemetryEvent::RunEnded { victory: true });
+        save::delete();
+        save::delete_checkpoint();
+        app_state.set(AppState::GameOver);
+        return;
+    };
+    // A locked or missing next realm strands the run, which is not a win
+    let next_realm = realms.and_then(|realms| realms.0.get(next));
+    if !next_realm.is_some_and(|realm| realm.requires.iter().all(|flag| run.flags.contains(flag))) {
+        if locked_query.is_empty() {
+            match next_realm {
+                Some(_) => {
+                    log::info!(realm = run.map.realm.0.as_str(); "Next realm {} is locked", next.0)
+                }
+                None => {
+                    log::error!(realm = run.map.realm.0.as_str(); "Missing next realm {}", next.0)
+                }
+            }
+            let name = next_realm.map_or(next.0.as_str(), |realm| realm.name.as_str());
+            spawn_locked_realm(&mut commands, &asset_server, &language, name);
+        }
+        return;
+    }
+    log::info!(
+        realm = run.map.realm.0.as_str(),
+        next = next.0.as_str();
+        "Leaving {} for {}", run.map.realm.0, next.0
+    );
+    let seed = run.rng.next_seed(RngStream::Map);
+    run.map = build_map(next, realms, &run, seed);
+    run.record.realms.push(next.clone());
+}
+
+fn spawn_locations(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    run: Res<RunState>,
+    locations_query: Query<Entity, Or<(With<Location>, With<RealmBackground>)>>,
+) {
+    let map = &run.map;
+    for location_entity in &locations_query {
+        commands.entity(location_entity).despawn();
+    }
+
+    if let Some(background) = &map.background {
+        commands.spawn((
+            RealmBackground,
+            Sprite::from_image(asset_server.load(background)),
+            Transform::from_translation(vec3(0., 0., Z_BACKGROUND)),
+        ));
+    }
+
+    let reachable = map.reachable(&run.flags);
+    for (i, map_node) in map.nodes.iter().enumerate() {
+        let node = NodeId(i);
+        let selectable = reachable.contains(&node);
+        let visited = map.current.is_some_and(|current| {
+            map.node(current)
+                .is_some_and(|current| current.layer >= map_node.layer)
+        });
+        let color = if selectable {
+            SELECTABLE
+        } else if visited {
+            VISITED
+        } else {
+            UNREACHABLE
+        };
+        let image = asset_server.load(map_node.image_path().to_string());
+        let position = location_position(map, node);
+        commands.spawn((
+            Location {
+                node,
+                title: map_node.title.clone(),
+                details: vec![map_node.node_type.title().to_string()],
+                image: image.clone(),
+                selectable,
+            },
+            InteractiveNode::fixed(),
+            Sprite {
+                image,
+                color,
+                custom_size: Some(Vec2::splat(LOCATION_SIZE)),
+                ..default()
+            },
+            Transform::from_translation(position.extend(Z_LOCATION)),
+        ));
+    }
+}
+
+fn draw_edges(run: Res<RunState>, mut gizmos: Gizmos) {
+    for (i, map_node) in run.map.nodes.iter().enumerate() {
+        let from = location_position(&run.map, NodeId(i));
+        for &edge in &map_node.edges {
+            gizmos.line_2d(from, location_position(&run.map, edge), EDGE);
+        }
+    }
+}
+
+fn handle_campaign(
+    mut commands: Commands,
+    mut node_interaction_events: EventReader<NodeInteraction>,
+    locations_query: Query<&Location>,
+    mut run: ResMut<RunState>,
+    actors_handle: Res<ActorLibraryHandle>,
+    actors: Res<Assets<ActorLibrary>>,
+    cards_handle: Res<CardLibraryHandle>,
+    cards: Res<Assets<CardLibrary>>,
+    dialogues_handle: Res<DialogueLibraryHandle>,
+    dialogues: Res<Assets<DialogueLibrary>>,
+    mut app_state: ResMut<NextState<AppState>>,
+) {
+    for interaction in node_interaction_events.read() {
+        if interaction.interaction_type != NodeInteractionType::LeftClick {
+            continue;
+        }
+        let Ok(location) = locations_query.get(interaction.entity) else {
+            continue;
+        };
+        if !location.selectable {
+            continue;
+        }
+        let Some(node_type) = run.map.node(location.node).map(|node| node.node_type) else {
+            continue;
+        };
+
+        run.map.selected = Some(location.node);
+        let dialogue = run
+            .map
+            .node(location.node)
+            .and_then(|node| node.dialogue.as_ref())
+            .zip(dialogues.get(dialogues_handle.0.id()))
+            .and_then(|(id, dialogues)| ActiveDialogue::begin(id, dialogues));
+        if let Some(dialogue) = dialogue {
+            commands.insert_resource(dialogue);
+            app_state.set(AppState::Dialogue);
+            break;
+        }
+        if let Some(script) = run
+            .map
+            .node(location.node)
+            .and_then(|node| node.event.clone())
+        {
+            commands.insert_resource(ActiveEvent::begin(script));
+            app_state.set(AppState::Event);
+            break;
+        }
+        // Gullveig waits in ambush before the Wellspring can be reached
+        let ambushed = node_type == NodeType::Wellspring
+            && run
+                .map
+                .node(location.node)
+                .is_some_and(|node| node.encounter.is_some());
+        let state = if ambushed {
+            Some(AppState::Battle)
+        } else {
+            node_type.app_state()
+        };
+        match state {
+            Some(state) => app_state.set(state),
+            None => {
+                log::info!(node = location.title.as_str(); "Resolved {} on the map", location.title);
+                let fate = run
+                    .map
+                    .node(location.node)
+                    .and_then(|node| node.encounter.as_ref())
+                    .and_then(|id| actors.get(actors_handle.0.id())?.0.get(id))
+                    .filter(|actor| actor.actor_type == ActorType::Fate);
+                if let (Some(fate), Some(cards)) = (fate, cards.get(cards_handle.0.id())) {
+                    for thread in &fate.grants {
+                        run.grant_thread(thread, cards);
+                    }
+                }
+                run.map.advance();
+                save::save(&run);
+            }
+        }
+        break;
+    }
+}
+
+fn teardown_campaign(
+    mut commands: Commands,
+    locations_query: Query<
+        Entity,
+        Or<(
+            With<Location>,
+            With<RealmBackground>,
+            With<SpawnedByScript>,
+            With<LockedRealmPanel>,
+        )>,
+    >,
+) {
+    for location_entity in &locations_query {
+        commands.entity(location_entity).despawn();
+    }
+}
 
 #[derive(Default)]
 pub struct CampaignPlugin;
@@ -22,7 +392,18 @@ pub struct CampaignPlugin;
 impl Plugin for CampaignPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(OnEnter(AppState::Campaign), setup_campaign)
-            .add_systems(Update, handle_campaign)
+            .add_systems(
+                Update,
+                (
+                    (advance_realm, spawn_locations)
+                        .chain()
+                        .run_if(resource_exists_and_changed::<RunState>),
+                    draw_edges,
+                    handle_campaign,
+                    handle_end_run,
+                )
+                    .run_if(in_state(AppState::Campaign)),
+            )
             .add_systems(OnExit(AppState::Campaign), teardown_campaign);
     }
 }
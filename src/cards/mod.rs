@@ -1,17 +1,24 @@
-mod event;
-mod script;
+pub mod event;
+pub mod script;
 mod system;
 mod utils;
 
-use bevy::prelude::*;
+use bevy::{ecs::system::EntityCommands, prelude::*};
 use std::time::Duration;
 
+use crate::asset::card::Card;
 use crate::cards::{event::EventsPlugin, script::ScriptsPlugin, system::SystemsPlugin};
+use crate::zindex::{Z_CARD, Z_D_CARD_ICON};
+
+const HOME_STRENGTH: f32 = 1.;
+/// Backing tint marking a card that has been upgraded
+const UPGRADED: Color = Color::srgb(1.0, 0.85, 0.45);
 
 #[derive(Default, Clone)]
 pub struct LerpTarget {
     position: Vec2,
     strength: f32,
+    zone: Option<Entity>,
 }
 
 impl LerpTarget {
@@ -60,6 +67,37 @@ pub struct InteractiveNode {
     lerp_target: LerpTarget,
     last_drop: Option<LerpTarget>,
     next_drop: Option<LerpTarget>,
+    home: Option<LerpTarget>,
+    fixed: bool,
+}
+
+impl InteractiveNode {
+    /// A node that reports interactions but is never dragged around
+    pub fn fixed() -> Self {
+        Self {
+            fixed: true,
+            ..default()
+        }
+    }
+
+    /// A node that returns to `position` when dropped outside of any drop zone
+    pub fn homed(position: Vec2) -> Self {
+        let home = LerpTarget {
+            position,
+            strength: HOME_STRENGTH,
+            zone: None,
+        };
+        Self {
+            lerp_target: home.clone(),
+            home: Some(home),
+            ..default()
+        }
+    }
+
+    /// The drop zone this node is currently over, or was dropped onto
+    pub fn drop_zone(&self) -> Option<Entity> {
+        self.next_drop.as_ref().and_then(|drop| drop.zone)
+    }
 }
 
 #[derive(Component)]
@@ -69,10 +107,39 @@ pub struct CardBacking {
 
 impl Default for CardBacking {
     fn default() -> Self {
-        Self { image: "cards/front.png".to_string() }
+        Self {
+            image: "cards/front.png".to_string(),
+        }
     }
 }
 
+pub fn spawn_card_node<'a>(
+    commands: &'a mut Commands,
+    asset_server: &AssetServer,
+    card: &Card,
+    node: InteractiveNode,
+    position: Vec2,
+) -> EntityCommands<'a> {
+    let backing = CardBacking::default();
+    let backing_image = asset_server.load(backing.image.clone());
+    let card_image = asset_server.load(card.image.clone());
+    let mut backing_sprite = Sprite::from_image(backing_image);
+    if card.level > 0 {
+        backing_sprite.color = UPGRADED;
+    }
+    let mut entity_commands = commands.spawn((
+        node,
+        backing_sprite,
+        Transform::from_translation(position.extend(Z_CARD)),
+    ));
+    entity_commands.with_child((
+        backing,
+        Sprite::from_image(card_image),
+        Transform::from_translation(vec3(0., 0., Z_D_CARD_ICON)),
+    ));
+    entity_commands
+}
+
 #[derive(Default)]
 struct HoldingState {
     duration: Duration,
@@ -80,6 +147,17 @@ struct HoldingState {
     is_holding: bool,
 }
 
+/// The one finger being tracked, as `HoldingState` for touches
+#[derive(Resource, Default)]
+struct TouchState {
+    id: Option<u64>,
+    start: Duration,
+    start_position: Vec2,
+    entity: Option<Entity>,
+    is_holding: bool,
+    long_pressed: bool,
+}
+
 #[derive(Default)]
 pub struct InteractiveCardsPlugin;
 
@@ -3,11 +3,10 @@ use bevy_scriptum::prelude::*;
 use bevy_scriptum::runtimes::lua::prelude::*;
 
 use crate::asset::card::{CardId, CardLibrary, CardLibraryHandle};
-use crate::cards::{CardBacking, InteractiveNode};
-use crate::zindex::{Z_CARD, Z_D_CARD_ICON};
+use crate::cards::{InteractiveNode, spawn_card_node};
 
 #[derive(Component)]
-struct SpawnedByScript;
+pub struct SpawnedByScript;
 
 #[derive(Component)]
 struct BackgroundNode;
@@ -22,22 +21,14 @@ fn spawn_card(
     if let Some(cards) = cards.get(cards_handle.0.id())
         && let Some(card) = cards.0.get(&CardId(id.clone()))
     {
-        let backing = CardBacking::default();
-        let backing_image = asset_server.load(backing.image.clone());
-        let card_image = asset_server.load(card.image.clone());
-        commands
-            .spawn((
-                InteractiveNode::default(),
-                SpawnedByScript,
-                Sprite::from_image(backing_image),
-                Transform::from_translation(vec3(0., 0., Z_CARD)),
-            ))
-            .with_child((
-                backing,
-                SpawnedByScript,
-                Sprite::from_image(card_image),
-                Transform::from_translation(vec3(0., 0., Z_CARD + Z_D_CARD_ICON)),
-            ));
+        spawn_card_node(
+            &mut commands,
+            &asset_server,
+            card,
+            InteractiveNode::default(),
+            Vec2::ZERO,
+        )
+        .insert(SpawnedByScript);
     } else {
         log::warn!("Missing asset while spawning {}", id.clone());
     }
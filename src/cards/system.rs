@@ -1,17 +1,23 @@
 use crate::MainCamera;
 use crate::cards::event::{NodeInteraction, NodeInteractionType};
-use crate::cards::{DropZoneNode, HoldingState, InteractiveNode, LerpTarget, utils::*};
+use crate::cards::{DropZoneNode, HoldingState, InteractiveNode, LerpTarget, TouchState, utils::*};
 use crate::zindex::Z_DEBUG;
-use bevy::{prelude::*, window::PrimaryWindow};
+use bevy::{input::touch::Touches, prelude::*, window::PrimaryWindow};
+use std::time::Duration;
 
 const LERP_STRENGTH: f32 = 2.;
+const LONG_PRESS_DURATION: Duration = Duration::from_millis(500);
+/// Logical pixels a finger may wander while still tapping or long-pressing
+const TAP_SLOP: f32 = 8.;
 
 fn follow_drag_event(
     mut node_interaction_events: EventReader<NodeInteraction>,
     windows: Query<&Window>,
+    touches: Res<Touches>,
+    touch_state: Res<TouchState>,
     camera: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
     mut sprite_query: Query<(&Sprite, &mut InteractiveNode, Entity)>,
-    drop_zone_query: Query<&DropZoneNode>,
+    drop_zone_query: Query<(Entity, &DropZoneNode)>,
 ) {
     let window = windows.single().unwrap();
     let (camera, camera_transform) = camera.single().unwrap();
@@ -23,15 +29,22 @@ fn follow_drag_event(
             .find(|&interaction| interaction.entity == entity)
         {
             match interaction.interaction_type {
-                NodeInteractionType::LeftDrag => {
-                    if let Some(cursor_transform) = window.cursor_position().and_then(|cursor| {
-                        camera.viewport_to_world_2d(camera_transform, cursor).ok()
-                    }) {
+                NodeInteractionType::LeftDrag if !interactive_node.fixed => {
+                    // The tracked finger takes precedence over the mouse cursor
+                    if let Some(cursor_transform) = (touch_state.id)
+                        .and_then(|id| touches.get_pressed(id))
+                        .map(|touch| touch.position())
+                        .or(window.cursor_position())
+                        .and_then(|cursor| {
+                            camera.viewport_to_world_2d(camera_transform, cursor).ok()
+                        })
+                    {
                         interactive_node.next_drop = lerp_drop_zone(cursor_transform, &drop_zones);
                         interactive_node.lerp_target =
                             interactive_node.next_drop.clone().unwrap_or(LerpTarget {
                                 position: cursor_transform,
                                 strength: LERP_STRENGTH,
+                                zone: None,
                             });
                     }
                 }
@@ -50,6 +63,11 @@ fn update_last_drop(
             && let Ok((mut interactive_node, _)) = sprite_query.get_mut(interaction_event.entity)
         {
             interactive_node.last_drop = interactive_node.next_drop.clone();
+            if interactive_node.last_drop.is_none()
+                && let Some(home) = interactive_node.home.clone()
+            {
+                interactive_node.lerp_target = home;
+            }
         }
     }
 }
@@ -125,6 +143,90 @@ fn drag_drop_sprite(
     }
 }
 
+fn touch_drag_drop_sprite(
+    touches: Res<Touches>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    res_images: Res<Assets<Image>>,
+    sprite_query: Query<(&Sprite, &GlobalTransform, Entity), With<InteractiveNode>>,
+    camera_query: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    mut node_interaction_events: EventWriter<NodeInteraction>,
+    mut touch_state: ResMut<TouchState>,
+) {
+    let primary_window = windows.single().unwrap();
+    let (camera, camera_transform) = camera_query.single().unwrap();
+    let now = Duration::from_millis(get_timestamp() as u64);
+
+    // Track only the first finger down, so a second never steals the drag
+    if touch_state.id.is_none()
+        && let Some(touch) = touches.iter_just_pressed().next()
+    {
+        *touch_state = TouchState {
+            id: Some(touch.id()),
+            start: now,
+            start_position: touch.position(),
+            entity: camera
+                .viewport_to_world_2d(camera_transform, touch.position())
+                .ok()
+                .and_then(|pos| {
+                    get_entity_at(
+                        pos,
+                        &res_images,
+                        &sprite_query,
+                        primary_window.scale_factor(),
+                    )
+                })
+                .map(|(entity, _)| entity),
+            is_holding: false,
+            long_pressed: false,
+        };
+    }
+    let Some(id) = touch_state.id else {
+        return;
+    };
+    let Some(entity) = touch_state.entity else {
+        if touches.get_pressed(id).is_none() {
+            *touch_state = TouchState::default();
+        }
+        return;
+    };
+
+    if let Some(touch) = touches.get_pressed(id) {
+        let held_for = now.saturating_sub(touch_state.start);
+        let moved = touch.position().distance(touch_state.start_position) > TAP_SLOP;
+        // Single-finger drag
+        if touch_state.is_holding || (moved && held_for > DRAG_DURATION) {
+            touch_state.is_holding = true;
+            node_interaction_events.write(NodeInteraction {
+                entity,
+                interaction_type: NodeInteractionType::LeftDrag,
+            });
+        } else if !moved && !touch_state.long_pressed && held_for > LONG_PRESS_DURATION {
+            // Long-press
+            touch_state.long_pressed = true;
+            node_interaction_events.write(NodeInteraction {
+                entity,
+                interaction_type: NodeInteractionType::RightClick,
+            });
+        }
+        return;
+    }
+
+    // Lifted or cancelled: drop a drag, or tap if nothing else happened
+    let canceled = touches.iter_just_canceled().any(|touch| touch.id() == id);
+    if touch_state.is_holding {
+        node_interaction_events.write(NodeInteraction {
+            entity,
+            interaction_type: NodeInteractionType::LeftDrop,
+        });
+    } else if !touch_state.long_pressed && !canceled {
+        node_interaction_events.write(NodeInteraction {
+            entity,
+            interaction_type: NodeInteractionType::LeftClick,
+        });
+    }
+    *touch_state = TouchState::default();
+}
+
 fn trace_dropzone(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
@@ -145,10 +247,12 @@ pub struct SystemsPlugin;
 
 impl Plugin for SystemsPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(PreUpdate, drag_drop_sprite).add_systems(
-            Update,
-            (follow_drag_event, lerp_to_target, update_last_drop),
-        );
+        app.init_resource::<TouchState>()
+            .add_systems(PreUpdate, (drag_drop_sprite, touch_drag_drop_sprite))
+            .add_systems(
+                Update,
+                (follow_drag_event, lerp_to_target, update_last_drop),
+            );
         #[cfg(feature = "dev_mode")]
         {
             app.add_systems(PreUpdate, (trace_dropzone,));
@@ -3,7 +3,7 @@ use crate::cards::{DropZoneNode, HoldingState, InteractiveNode, LerpTarget};
 use bevy::prelude::*;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
-const DRAG_DURATION: Duration = Duration::from_millis(60);
+pub const DRAG_DURATION: Duration = Duration::from_millis(60);
 const LERP_STRENGTH: f32 = 0.999;
 
 pub fn get_timestamp() -> u128 {
@@ -11,15 +11,19 @@ pub fn get_timestamp() -> u128 {
     duration.as_millis()
 }
 
-pub fn lerp_drop_zone(cursor: Vec2, drop_zones: &Vec<&DropZoneNode>) -> Option<LerpTarget> {
+pub fn lerp_drop_zone(
+    cursor: Vec2,
+    drop_zones: &Vec<(Entity, &DropZoneNode)>,
+) -> Option<LerpTarget> {
     let mut intersects = drop_zones
         .iter()
-        .filter(|&drop_zone| drop_zone.contains(cursor))
+        .filter(|&(_, drop_zone)| drop_zone.contains(cursor))
         .collect::<Vec<_>>();
-    intersects.sort_by(|&x, &y| x.distance(cursor).total_cmp(&y.distance(cursor)));
-    intersects.first().map(|&drop_zone| LerpTarget {
+    intersects.sort_by(|&(_, x), &(_, y)| x.distance(cursor).total_cmp(&y.distance(cursor)));
+    intersects.first().map(|&&(entity, drop_zone)| LerpTarget {
         position: drop_zone.position,
         strength: LERP_STRENGTH,
+        zone: Some(entity),
     })
 }
 
@@ -139,46 +143,54 @@ pub fn get_active_entity(
     camera_transform: &GlobalTransform,
     scale_factor: f32,
 ) -> Option<(Entity, f32)> {
-    let mut active_entity = None;
     if !cursor_moved_events.is_empty() || buttons.get_pressed().size_hint().0 > 0 {
-        for (sprite, node_transform, entity) in sprite_query.iter() {
-            let size = match sprite.custom_size {
-                Some(size) => (size.x, size.y),
-                None => {
-                    if let Some(sprite_image) = res_images.get(&sprite.image) {
-                        (
-                            sprite_image.size().x as f32 / scale_factor,
-                            sprite_image.size().y as f32 / scale_factor,
-                        )
-                    } else {
-                        (1., 1.)
-                    }
+        let pos = primary_window
+            .cursor_position()
+            .and_then(|pos| camera.viewport_to_world_2d(camera_transform, pos).ok())?;
+        return get_entity_at(pos, &res_images, &sprite_query, scale_factor);
+    }
+    None
+}
+
+/// The topmost interactive sprite under a point in world space
+pub fn get_entity_at(
+    pos: Vec2,
+    res_images: &Assets<Image>,
+    sprite_query: &Query<'_, '_, (&Sprite, &GlobalTransform, Entity), With<InteractiveNode>>,
+    scale_factor: f32,
+) -> Option<(Entity, f32)> {
+    let mut active_entity = None;
+    for (sprite, node_transform, entity) in sprite_query.iter() {
+        let size = match sprite.custom_size {
+            Some(size) => (size.x, size.y),
+            None => {
+                if let Some(sprite_image) = res_images.get(&sprite.image) {
+                    (
+                        sprite_image.size().x as f32 / scale_factor,
+                        sprite_image.size().y as f32 / scale_factor,
+                    )
+                } else {
+                    (1., 1.)
                 }
-            };
+            }
+        };
 
-            // Find the bounds for this sprite
-            let x_min = node_transform.affine().translation.x - size.0 / 2.;
-            let y_min = node_transform.affine().translation.y - size.1 / 2.;
-            let x_max = node_transform.affine().translation.x + size.0 / 2.;
-            let y_max = node_transform.affine().translation.y + size.1 / 2.;
-            let z_current = node_transform.affine().translation.z;
+        // Find the bounds for this sprite
+        let x_min = node_transform.affine().translation.x - size.0 / 2.;
+        let y_min = node_transform.affine().translation.y - size.1 / 2.;
+        let x_max = node_transform.affine().translation.x + size.0 / 2.;
+        let y_max = node_transform.affine().translation.y + size.1 / 2.;
+        let z_current = node_transform.affine().translation.z;
 
-            if let Some(pos) = primary_window.cursor_position()
-                && let Ok(pos) = camera.viewport_to_world_2d(camera_transform, pos)
-                && x_min < pos.x
-                && pos.x < x_max
-                && y_min < pos.y
-                && pos.y < y_max
-            {
-                if let Some((_, z)) = active_entity {
-                    // Prefer the largest z-index if covering two sprites
-                    if z < z_current {
-                        active_entity = Some((entity, z_current));
-                    }
-                } else {
-                    // Pick this if just one sprite
-                    active_entity = Some((entity, node_transform.affine().translation.z));
+        if x_min < pos.x && pos.x < x_max && y_min < pos.y && pos.y < y_max {
+            if let Some((_, z)) = active_entity {
+                // Prefer the largest z-index if covering two sprites
+                if z < z_current {
+                    active_entity = Some((entity, z_current));
                 }
+            } else {
+                // Pick this if just one sprite
+                active_entity = Some((entity, node_transform.affine().translation.z));
             }
         }
     }
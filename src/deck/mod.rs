@@ -0,0 +1,228 @@
+use bevy::prelude::*;
+
+use crate::asset::card::{CardLibrary, CardLibraryHandle};
+use crate::input::BackButton;
+use crate::menu::resource::Language;
+use crate::menu::{BACKDROP, NORMAL_BUTTON, TEXT};
+use crate::run::RunState;
+use crate::state::AppState;
+
+const CARD_ART_SIZE: f32 = 48.;
+const UPGRADED_TEXT: Color = Color::srgb(1.0, 0.85, 0.45);
+
+/// What picking a card from the run deck does
+#[derive(Resource, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DeckAction {
+    Upgrade,
+    /// Remove a card for good, paying the shop's price
+    Remove {
+        price: u32,
+    },
+}
+
+impl DeckAction {
+    fn title(&self) -> String {
+        match self {
+            Self::Upgrade => "choose a card to upgrade".to_string(),
+            Self::Remove { price } => format!("choose a card to remove for {} silver", price),
+        }
+    }
+
+    /// The screen to return to once a card is picked or the deck is left
+    fn return_state(&self) -> AppState {
+        match self {
+            Self::Upgrade => AppState::Campaign,
+            Self::Remove { .. } => AppState::Shop,
+        }
+    }
+}
+
+#[derive(Component)]
+struct OnDeckScreen;
+
+#[derive(Component)]
+enum DeckButtonAction {
+    Pick(usize),
+    Leave,
+}
+
+fn setup_deck(
+    mut commands: Commands,
+    language: Res<Language>,
+    asset_server: Res<AssetServer>,
+    action: Res<DeckAction>,
+    run: Res<RunState>,
+    cards_handle: Res<CardLibraryHandle>,
+    cards: Res<Assets<CardLibrary>>,
+) {
+    let Some(cards) = cards.get(cards_handle.0.id()) else {
+        log::warn!("Missing card library while spawning deck");
+        return;
+    };
+
+    let font = asset_server.load(language.font_path());
+    let text_font = TextFont {
+        font_size: 14.0,
+        font: font.clone(),
+        ..default()
+    };
+    let card_node = Node {
+        width: Val::Px(96.0),
+        margin: UiRect::all(Val::Px(4.0)),
+        padding: UiRect::all(Val::Px(4.0)),
+        flex_direction: FlexDirection::Column,
+        align_items: AlignItems::Center,
+        ..default()
+    };
+
+    let grid = commands
+        .spawn(Node {
+            flex_wrap: FlexWrap::Wrap,
+            justify_content: JustifyContent::Center,
+            max_width: Val::Percent(90.0),
+            ..default()
+        })
+        .id();
+    for (index, owned) in run.deck.iter().enumerate() {
+        let picked = match *action {
+            DeckAction::Upgrade if run.can_upgrade(index, cards) => cards
+                .0
+                .get(&owned.id)
+                .map(|card| format!("> {}", card.at_level(owned.level + 1).name)),
+            DeckAction::Upgrade => None,
+            DeckAction::Remove { .. } => Some("remove".to_string()),
+        };
+        let (Some(card), Some(picked)) = (cards.resolve(owned), picked) else {
+            continue;
+        };
+        let button = commands
+            .spawn((
+                Button,
+                card_node.clone(),
+                BackgroundColor(NORMAL_BUTTON),
+                DeckButtonAction::Pick(index),
+                children![
+                    (
+                        Node {
+                            width: Val::Px(CARD_ART_SIZE),
+                            height: Val::Px(CARD_ART_SIZE),
+                            ..default()
+                        },
+                        ImageNode::new(asset_server.load(card.image.clone())),
+                    ),
+                    (
+                        Text::new(card.name.clone()),
+                        text_font.clone(),
+                        TextColor(if card.level > 0 { UPGRADED_TEXT } else { TEXT }),
+                    ),
+                    (
+                        Text::new(picked),
+                        text_font.clone(),
+                        TextColor(UPGRADED_TEXT),
+                    ),
+                ],
+            ))
+            .id();
+        commands.entity(grid).add_child(button);
+    }
+
+    commands
+        .spawn((
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            BackgroundColor(BACKDROP),
+            OnDeckScreen,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new(action.title()),
+                TextFont {
+                    font_size: 24.0,
+                    font,
+                    ..default()
+                },
+                TextColor(TEXT),
+            ));
+        })
+        .add_child(grid)
+        .with_children(|parent| {
+            parent.spawn((
+                Button,
+                Node {
+                    margin: UiRect::all(Val::Px(8.0)),
+                    padding: UiRect::horizontal(Val::Px(8.0)),
+                    ..default()
+                },
+                BackgroundColor(NORMAL_BUTTON),
+                DeckButtonAction::Leave,
+                BackButton,
+                children![(Text::new("leave"), text_font, TextColor(TEXT))],
+            ));
+        });
+}
+
+fn handle_deck_buttons(
+    interaction_query: Query<
+        (&Interaction, &DeckButtonAction),
+        (Changed<Interaction>, With<Button>),
+    >,
+    action: Res<DeckAction>,
+    mut run: ResMut<RunState>,
+    cards_handle: Res<CardLibraryHandle>,
+    cards: Res<Assets<CardLibrary>>,
+    mut app_state: ResMut<NextState<AppState>>,
+) {
+    for (interaction, deck_button_action) in &interaction_query {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        if let DeckButtonAction::Pick(index) = deck_button_action {
+            let Some(cards) = cards.get(cards_handle.0.id()) else {
+                log::warn!("Missing card library while picking from deck");
+                continue;
+            };
+            match *action {
+                DeckAction::Upgrade => {
+                    run.upgrade(*index, cards);
+                }
+                DeckAction::Remove { price } => {
+                    if *index < run.deck.len() && run.can_remove_card() && run.spend_silver(price) {
+                        run.remove_card(*index);
+                    }
+                }
+            }
+        }
+        app_state.set(action.return_state());
+        break;
+    }
+}
+
+fn teardown_deck(mut commands: Commands, deck_items_query: Query<Entity, With<OnDeckScreen>>) {
+    for deck_entity in &deck_items_query {
+        commands.entity(deck_entity).despawn();
+    }
+    commands.remove_resource::<DeckAction>();
+}
+
+#[derive(Default)]
+pub struct DeckPlugin;
+
+impl Plugin for DeckPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            OnEnter(AppState::Deck),
+            setup_deck.run_if(resource_exists::<DeckAction>),
+        )
+        .add_systems(
+            Update,
+            handle_deck_buttons.run_if(in_state(AppState::Deck).and(resource_exists::<DeckAction>)),
+        )
+        .add_systems(OnExit(AppState::Deck), teardown_deck);
+    }
+}
@@ -0,0 +1,439 @@
+use bevy::prelude::*;
+use bevy_scriptum::prelude::*;
+use bevy_scriptum::runtimes::lua::prelude::*;
+
+use crate::asset::actor::{ActorLibrary, ActorLibraryHandle};
+use crate::asset::card::{CardLibrary, CardLibraryHandle};
+use crate::asset::clan::{ClanLibrary, ClanLibraryHandle};
+use crate::asset::class::{ClassLibrary, ClassLibraryHandle};
+use crate::asset::dialogue::{
+    DialogueEffect, DialogueId, DialogueLibrary, DialogueLibraryHandle, DialogueNode,
+};
+use crate::battle::combat::BattleKind;
+use crate::menu::resource::Language;
+use crate::menu::{BACKDROP, NORMAL_BUTTON, TEXT};
+use crate::profile::Profile;
+use crate::run::telemetry::{self, TelemetryEvent};
+use crate::run::{Encounter, RunState};
+use crate::state::AppState;
+
+const FACE_SIZE: f32 = 96.;
+
+/// The dialogue being shown and the line it has reached
+#[derive(Resource, Clone, Debug)]
+pub struct ActiveDialogue {
+    pub dialogue: DialogueId,
+    pub node: String,
+}
+
+impl ActiveDialogue {
+    pub fn begin(id: &DialogueId, dialogues: &DialogueLibrary) -> Option<Self> {
+        let Some(dialogue) = dialogues.0.get(id) else {
+            log::warn!("Missing dialogue {}", id.0);
+            return None;
+        };
+        Some(Self {
+            dialogue: id.clone(),
+            node: dialogue.start.clone(),
+        })
+    }
+}
+
+#[derive(Component)]
+struct OnDialogueScreen;
+
+#[derive(Component)]
+enum DialogueButtonAction {
+    Choose(usize),
+    Continue,
+}
+
+fn current_node<'a>(
+    active: &ActiveDialogue,
+    dialogues: &'a DialogueLibrary,
+) -> Option<&'a DialogueNode> {
+    dialogues
+        .0
+        .get(&active.dialogue)
+        .and_then(|dialogue| dialogue.nodes.get(&active.node))
+}
+
+fn spawn_dialogue_ui(
+    mut commands: Commands,
+    language: Res<Language>,
+    asset_server: Res<AssetServer>,
+    active: Res<ActiveDialogue>,
+    run: Res<RunState>,
+    dialogues_handle: Res<DialogueLibraryHandle>,
+    dialogues: Res<Assets<DialogueLibrary>>,
+    actors_handle: Res<ActorLibraryHandle>,
+    actors: Res<Assets<ActorLibrary>>,
+    classes_handle: Res<ClassLibraryHandle>,
+    classes: Res<Assets<ClassLibrary>>,
+    dialogue_items_query: Query<Entity, With<OnDialogueScreen>>,
+) {
+    for dialogue_entity in &dialogue_items_query {
+        commands.entity(dialogue_entity).despawn();
+    }
+    let Some(dialogues) = dialogues.get(dialogues_handle.0.id()) else {
+        log::warn!("Missing dialogue library while spawning dialogue");
+        return;
+    };
+    let Some(node) = current_node(&active, dialogues) else {
+        log::warn!("Missing node {} in {}", active.node, active.dialogue.0);
+        return;
+    };
+    let speaker = node
+        .speaker
+        .as_ref()
+        .or_else(|| dialogues.0.get(&active.dialogue).map(|d| &d.speaker))
+        .and_then(|id| actors.get(actors_handle.0.id())?.0.get(id));
+
+    let can_prestige = classes
+        .get(classes_handle.0.id())
+        .and_then(|classes| classes.0.get(&run.class))
+        .is_some_and(|class| class.can_prestige(&run));
+
+    let font = asset_server.load(language.font_path());
+    let text_font = TextFont {
+        font_size: 16.0,
+        font: font.clone(),
+        ..default()
+    };
+    let button_node = Node {
+        min_height: Val::Px(28.0),
+        margin: UiRect::top(Val::Px(6.0)),
+        padding: UiRect::horizontal(Val::Px(8.0)),
+        align_items: AlignItems::Center,
+        ..default()
+    };
+
+    let lines = commands
+        .spawn((
+            Node {
+                flex_direction: FlexDirection::Column,
+                flex_grow: 1.0,
+                margin: UiRect::left(Val::Px(12.0)),
+                ..default()
+            },
+            children![
+                (
+                    Text::new(speaker.map(|actor| actor.name.clone()).unwrap_or_default()),
+                    text_font.clone(),
+                    TextColor(TEXT),
+                ),
+                (
+                    Text::new(node.text.clone()),
+                    text_font.clone(),
+                    TextColor(TEXT)
+                ),
+            ],
+        ))
+        .id();
+
+    let choices = node
+        .choices
+        .iter()
+        .enumerate()
+        .filter(|(_, choice)| choice.conditions.iter().all(|c| c.met(&run)))
+        .filter(|(_, choice)| !choice.prestiges() || can_prestige)
+        .map(|(i, choice)| {
+            let text = match &choice.check {
+                Some(check) => format!(
+                    "[{} {:.0}%] {}",
+                    check.target,
+                    check.odds(&run) * 100.,
+                    choice.text
+                ),
+                None => choice.text.clone(),
+            };
+            (DialogueButtonAction::Choose(i), text)
+        })
+        .collect::<Vec<_>>();
+    let buttons = if choices.is_empty() {
+        vec![(DialogueButtonAction::Continue, "continue".to_string())]
+    } else {
+        choices
+    };
+    for (action, text) in buttons {
+        let button = commands
+            .spawn((
+                Button,
+                button_node.clone(),
+                BackgroundColor(NORMAL_BUTTON),
+                action,
+                children![(Text::new(text), text_font.clone(), TextColor(TEXT))],
+            ))
+            .id();
+        commands.entity(lines).add_child(button);
+    }
+
+    let face = commands
+        .spawn((
+            Node {
+                width: Val::Px(FACE_SIZE),
+                height: Val::Px(FACE_SIZE),
+                flex_shrink: 0.0,
+                ..default()
+            },
+            ImageNode::new(
+                speaker
+                    .map(|actor| asset_server.load(actor.face_image.clone()))
+                    .unwrap_or_default(),
+            ),
+        ))
+        .id();
+
+    commands
+        .spawn((
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                align_items: AlignItems::FlexEnd,
+                ..default()
+            },
+            OnDialogueScreen,
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn((
+                    Node {
+                        width: Val::Percent(100.0),
+                        padding: UiRect::all(Val::Px(12.0)),
+                        ..default()
+                    },
+                    BackgroundColor(BACKDROP),
+                ))
+                .add_children(&[face, lines]);
+        });
+}
+
+/// The libraries dialogue effects may need to look into
+struct EffectLibraries<'a> {
+    cards: Option<&'a CardLibrary>,
+    classes: Option<&'a ClassLibrary>,
+    clans: Option<&'a ClanLibrary>,
+}
+
+fn apply_effect(
+    effect: &DialogueEffect,
+    run: &mut RunState,
+    profile: &mut Profile,
+    libraries: &EffectLibraries,
+) {
+    log::info!("Applying dialogue effect {:?}", effect);
+    match effect {
+        DialogueEffect::SetFlag(flag) => {
+            run.flags.insert(flag.clone());
+        }
+        DialogueEffect::ClearFlag(flag) => {
+            run.flags.remove(flag);
+        }
+        DialogueEffect::GainInfluence(influence, amount) => {
+            run.gain_influence(*influence, *amount);
+        }
+        DialogueEffect::ModifyStat(resource, amount) => run.stats.modify(*resource, *amount),
+        DialogueEffect::GrantThread(card) => match libraries.cards {
+            Some(cards) => {
+                run.grant_thread(card, cards);
+            }
+            None => log::warn!("Missing card library while granting {}", card.0),
+        },
+        DialogueEffect::StartBattle(actor) => {
+            run.encounter = Some(Encounter {
+                actor: actor.clone(),
+                kind: BattleKind::Combat,
+            });
+        }
+        DialogueEffect::StartNegotiation(actor) => {
+            run.encounter = Some(Encounter {
+                actor: actor.clone(),
+                kind: BattleKind::Negotiation,
+            });
+        }
+        DialogueEffect::Prestige { upgrade_cards } => {
+            let Some(classes) = libraries.classes else {
+                log::warn!("Missing class library while prestiging");
+                return;
+            };
+            let Some(prestige) = classes
+                .0
+                .get(&run.class)
+                .filter(|class| class.can_prestige(run))
+                .and_then(|class| class.prestige.as_ref())
+            else {
+                log::warn!("{} cannot prestige", run.class.0);
+                return;
+            };
+            let Some(into) = classes.0.get(&prestige.into) else {
+                log::warn!("Missing prestige class {}", prestige.into.0);
+                return;
+            };
+            let upgrades = upgrade_cards.then_some(&prestige.upgrades);
+            run.prestige(&prestige.into, into, upgrades);
+        }
+        DialogueEffect::SwearOath(clan) => {
+            match libraries.clans.and_then(|clans| clans.0.get(clan)) {
+                Some(oath) => {
+                    run.swear_oath(*clan, oath);
+                }
+                None => log::warn!("Missing clan {:?}", clan),
+            }
+        }
+        DialogueEffect::BreakOath => {
+            let oath = run
+                .oath
+                .zip(libraries.clans)
+                .and_then(|(oath, clans)| clans.0.get(&oath.clan));
+            match oath {
+                Some(oath) => {
+                    run.break_oath(oath);
+                }
+                None => log::warn!("No oath to break"),
+            }
+        }
+        DialogueEffect::GrantArtefact(artefact) => {
+            run.grant_artefact(artefact);
+        }
+        DialogueEffect::UpgradeCard(card) => match libraries.cards {
+            Some(cards) => {
+                run.upgrade_card(card, cards);
+            }
+            None => log::warn!("Missing card library while upgrading {}", card.0),
+        },
+        DialogueEffect::Unlock(unlock) => profile.unlock(unlock.clone()),
+    }
+}
+
+fn handle_dialogue_buttons(
+    interaction_query: Query<
+        (&Interaction, &DialogueButtonAction),
+        (Changed<Interaction>, With<Button>),
+    >,
+    mut commands: Commands,
+    mut active: ResMut<ActiveDialogue>,
+    mut run: ResMut<RunState>,
+    mut profile: ResMut<Profile>,
+    dialogues_handle: Res<DialogueLibraryHandle>,
+    dialogues: Res<Assets<DialogueLibrary>>,
+    cards_handle: Res<CardLibraryHandle>,
+    cards: Res<Assets<CardLibrary>>,
+    classes_handle: Res<ClassLibraryHandle>,
+    classes: Res<Assets<ClassLibrary>>,
+    clans_handle: Res<ClanLibraryHandle>,
+    clans: Res<Assets<ClanLibrary>>,
+    mut app_state: ResMut<NextState<AppState>>,
+) {
+    for (interaction, dialogue_button_action) in &interaction_query {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        let node = dialogues
+            .get(dialogues_handle.0.id())
+            .and_then(|dialogues| current_node(&active, dialogues));
+        let next = match (node, dialogue_button_action) {
+            (Some(node), DialogueButtonAction::Choose(i)) => {
+                node.choices.get(*i).and_then(|choice| {
+                    telemetry::record(
+                        &run,
+                        TelemetryEvent::Choice {
+                            source: active.dialogue.0.clone(),
+                            choice: choice.text.clone(),
+                        },
+                    );
+                    let libraries = EffectLibraries {
+                        cards: cards.get(cards_handle.0.id()),
+                        classes: classes.get(classes_handle.0.id()),
+                        clans: clans.get(clans_handle.0.id()),
+                    };
+                    for effect in &choice.effects {
+                        apply_effect(effect, &mut run, &mut profile, &libraries);
+                    }
+                    let Some(check) = &choice.check else {
+                        return choice.next.clone();
+                    };
+                    let passed = check.roll(&mut run);
+                    log::info!(
+                        "Rolled {:?} against {}: {}",
+                        check.target,
+                        check.difficulty,
+                        if passed { "passed" } else { "failed" }
+                    );
+                    let (effects, next) = if passed {
+                        (&check.success_effects, &check.success)
+                    } else {
+                        (&check.failure_effects, &check.failure)
+                    };
+                    for effect in effects {
+                        apply_effect(effect, &mut run, &mut profile, &libraries);
+                    }
+                    next.clone()
+                })
+            }
+            (Some(node), DialogueButtonAction::Continue) => node.next.clone(),
+            (None, _) => None,
+        };
+
+        match next {
+            Some(next) => active.node = next,
+            None => {
+                log::info!("Finished dialogue {}", active.dialogue.0);
+                commands.remove_resource::<ActiveDialogue>();
+                app_state.set(match run.encounter {
+                    Some(_) => AppState::Battle,
+                    None => AppState::Campaign,
+                });
+            }
+        }
+        break;
+    }
+}
+
+fn start_dialogue(
+    In((id,)): In<(String,)>,
+    mut commands: Commands,
+    dialogues_handle: Res<DialogueLibraryHandle>,
+    dialogues: Res<Assets<DialogueLibrary>>,
+    mut app_state: ResMut<NextState<AppState>>,
+) {
+    if let Some(active) = dialogues
+        .get(dialogues_handle.0.id())
+        .and_then(|dialogues| ActiveDialogue::begin(&DialogueId(id.clone()), dialogues))
+    {
+        commands.insert_resource(active);
+        app_state.set(AppState::Dialogue);
+    } else {
+        log::warn!("Missing asset while starting dialogue {}", id);
+    }
+}
+
+fn teardown_dialogue(
+    mut commands: Commands,
+    dialogue_items_query: Query<Entity, With<OnDialogueScreen>>,
+) {
+    for dialogue_entity in &dialogue_items_query {
+        commands.entity(dialogue_entity).despawn();
+    }
+    commands.remove_resource::<ActiveDialogue>();
+}
+
+#[derive(Default)]
+pub struct DialoguePlugin;
+
+impl Plugin for DialoguePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_scripting_api::<LuaRuntime>(|rt| {
+            rt.add_function("start_dialogue".into(), start_dialogue);
+        })
+        .add_systems(
+            Update,
+            (
+                spawn_dialogue_ui.run_if(resource_exists_and_changed::<ActiveDialogue>),
+                handle_dialogue_buttons.run_if(resource_exists::<ActiveDialogue>),
+            )
+                .chain()
+                .run_if(in_state(AppState::Dialogue)),
+        )
+        .add_systems(OnExit(AppState::Dialogue), teardown_dialogue);
+    }
+}
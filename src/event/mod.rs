@@ -0,0 +1,297 @@
+use bevy::prelude::*;
+use bevy_scriptum::prelude::*;
+use bevy_scriptum::runtimes::lua::prelude::*;
+
+use crate::asset::actor::ActorId;
+use crate::asset::card::{CardId, CardLibrary, CardLibraryHandle};
+use crate::asset::stats;
+use crate::battle::combat::BattleKind;
+use crate::menu::resource::Language;
+use crate::menu::{BACKDROP, NORMAL_BUTTON, TEXT};
+use crate::run::telemetry::{self, TelemetryEvent};
+use crate::run::{Encounter, RunState};
+use crate::state::AppState;
+
+const EVENT_IMAGE_SIZE: f32 = 96.;
+
+#[derive(Clone, Debug)]
+struct EventChoice {
+    text: String,
+    // Script run when chosen, ending the event if absent
+    script: Option<String>,
+}
+
+/// The page of a Lua event script being shown, filled in by the script
+/// through the event scripting API
+#[derive(Resource, Clone, Debug, Default)]
+pub struct ActiveEvent {
+    script: String,
+    text: Vec<String>,
+    image: Option<String>,
+    choices: Vec<EventChoice>,
+}
+
+impl ActiveEvent {
+    pub fn begin(script: String) -> Self {
+        Self {
+            script,
+            ..default()
+        }
+    }
+}
+
+#[derive(Component)]
+struct OnEventScreen;
+
+// Index of the chosen choice, or None to end an event without choices
+#[derive(Component)]
+struct EventButtonAction(Option<usize>);
+
+fn run_event_script(commands: &mut Commands, asset_server: &AssetServer, script: &str) {
+    log::info!("Running event script {}", script);
+    commands.spawn((
+        Script::<LuaScript>::new(asset_server.load(script.to_string())),
+        OnEventScreen,
+    ));
+}
+
+fn setup_event(mut commands: Commands, asset_server: Res<AssetServer>, event: Res<ActiveEvent>) {
+    run_event_script(&mut commands, &asset_server, &event.script);
+}
+
+fn spawn_event_ui(
+    mut commands: Commands,
+    language: Res<Language>,
+    asset_server: Res<AssetServer>,
+    event: Res<ActiveEvent>,
+    event_ui_query: Query<Entity, (With<OnEventScreen>, With<Node>)>,
+) {
+    for event_entity in &event_ui_query {
+        commands.entity(event_entity).despawn();
+    }
+
+    let text_font = TextFont {
+        font_size: 16.0,
+        font: asset_server.load(language.font_path()),
+        ..default()
+    };
+    let panel = commands
+        .spawn((
+            Node {
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                max_width: Val::Percent(80.0),
+                padding: UiRect::all(Val::Px(12.0)),
+                ..default()
+            },
+            BackgroundColor(BACKDROP),
+        ))
+        .id();
+    if let Some(image) = &event.image {
+        let image = commands
+            .spawn((
+                Node {
+                    width: Val::Px(EVENT_IMAGE_SIZE),
+                    height: Val::Px(EVENT_IMAGE_SIZE),
+                    ..default()
+                },
+                ImageNode::new(asset_server.load(image.clone())),
+            ))
+            .id();
+        commands.entity(panel).add_child(image);
+    }
+    for line in &event.text {
+        let text = commands
+            .spawn((Text::new(line.clone()), text_font.clone(), TextColor(TEXT)))
+            .id();
+        commands.entity(panel).add_child(text);
+    }
+    let buttons = if event.choices.is_empty() {
+        vec![(None, "continue".to_string())]
+    } else {
+        (event.choices.iter().enumerate())
+            .map(|(i, choice)| (Some(i), choice.text.clone()))
+            .collect()
+    };
+    for (index, text) in buttons {
+        let button = commands
+            .spawn((
+                Button,
+                Node {
+                    min_height: Val::Px(28.0),
+                    margin: UiRect::top(Val::Px(6.0)),
+                    padding: UiRect::horizontal(Val::Px(8.0)),
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                BackgroundColor(NORMAL_BUTTON),
+                EventButtonAction(index),
+                children![(Text::new(text), text_font.clone(), TextColor(TEXT))],
+            ))
+            .id();
+        commands.entity(panel).add_child(button);
+    }
+
+    commands
+        .spawn((
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            OnEventScreen,
+        ))
+        .add_child(panel);
+}
+
+fn handle_event_buttons(
+    mut commands: Commands,
+    interaction_query: Query<
+        (&Interaction, &EventButtonAction),
+        (Changed<Interaction>, With<Button>),
+    >,
+    asset_server: Res<AssetServer>,
+    mut event: ResMut<ActiveEvent>,
+    run: Res<RunState>,
+    mut app_state: ResMut<NextState<AppState>>,
+) {
+    for (interaction, EventButtonAction(index)) in &interaction_query {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        let Some(index) = index else {
+            app_state.set(AppState::Campaign);
+            break;
+        };
+        let Some(choice) = event.choices.get(*index).cloned() else {
+            continue;
+        };
+        log::info!("Chose {}", choice.text);
+        telemetry::record(
+            &run,
+            TelemetryEvent::Choice {
+                source: event.script.clone(),
+                choice: choice.text.clone(),
+            },
+        );
+        match choice.script {
+            Some(script) => {
+                *event = ActiveEvent::begin(script);
+                run_event_script(&mut commands, &asset_server, &event.script);
+            }
+            None => app_state.set(AppState::Campaign),
+        }
+        break;
+    }
+}
+
+fn show_event_text(In((text,)): In<(String,)>, event: Option<ResMut<ActiveEvent>>) {
+    match event {
+        Some(mut event) => event.text.push(text),
+        None => log::warn!("No event to show text: {}", text),
+    }
+}
+
+fn show_event_image(In((path,)): In<(String,)>, event: Option<ResMut<ActiveEvent>>) {
+    match event {
+        Some(mut event) => event.image = Some(path),
+        None => log::warn!("No event to show image {}", path),
+    }
+}
+
+/// Offer a choice running `script` when taken, or ending the event if empty
+fn add_choice(In((text, script)): In<(String, String)>, event: Option<ResMut<ActiveEvent>>) {
+    let Some(mut event) = event else {
+        log::warn!("No event to add choice: {}", text);
+        return;
+    };
+    let script = Some(script).filter(|script| !script.is_empty());
+    event.choices.push(EventChoice { text, script });
+}
+
+/// Add a card to the run deck, holding Fate Threads to the free Thread Slots
+fn grant_card(
+    In((id,)): In<(String,)>,
+    mut run: ResMut<RunState>,
+    cards_handle: Res<CardLibraryHandle>,
+    cards: Res<Assets<CardLibrary>>,
+) {
+    let id = CardId(id);
+    let Some(cards) = cards.get(cards_handle.0.id()) else {
+        log::warn!("Missing card library while granting {}", id.0);
+        return;
+    };
+    match cards.0.get(&id) {
+        Some(card) if card.thread.is_some() => {
+            run.grant_thread(&id, cards);
+        }
+        Some(_) => {
+            log::info!("Granted {}", id.0);
+            run.deck.push(id.into());
+        }
+        None => log::warn!("Missing card {}", id.0),
+    }
+}
+
+/// Modify a run resource, leaving at least 1 Health since events cannot end a run
+fn modify_stat(In((resource, amount)): In<(String, i64)>, mut run: ResMut<RunState>) {
+    match serde_json::from_value::<stats::Resource>(serde_json::Value::String(resource.clone())) {
+        Ok(stats::Resource::Health) => {
+            let amount = amount.max(1 - run.stats.get(stats::Resource::Health));
+            run.stats.modify(stats::Resource::Health, amount);
+        }
+        Ok(resource) => run.stats.modify(resource, amount),
+        Err(err) => log::warn!("Unknown resource {}: {}", resource, err),
+    }
+}
+
+fn start_battle(
+    In((actor,)): In<(String,)>,
+    mut run: ResMut<RunState>,
+    mut app_state: ResMut<NextState<AppState>>,
+) {
+    run.encounter = Some(Encounter {
+        actor: ActorId(actor),
+        kind: BattleKind::Combat,
+    });
+    app_state.set(AppState::Battle);
+}
+
+fn teardown_event(mut commands: Commands, event_items_query: Query<Entity, With<OnEventScreen>>) {
+    for event_entity in &event_items_query {
+        commands.entity(event_entity).despawn();
+    }
+    commands.remove_resource::<ActiveEvent>();
+}
+
+#[derive(Default)]
+pub struct EventPlugin;
+
+impl Plugin for EventPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_scripting_api::<LuaRuntime>(|rt| {
+            rt.add_function("show_event_text".into(), show_event_text)
+                .add_function("show_event_image".into(), show_event_image)
+                .add_function("add_choice".into(), add_choice)
+                .add_function("grant_card".into(), grant_card)
+                .add_function("modify_stat".into(), modify_stat)
+                .add_function("start_battle".into(), start_battle);
+        })
+        .add_systems(
+            OnEnter(AppState::Event),
+            setup_event.run_if(resource_exists::<ActiveEvent>),
+        )
+        .add_systems(
+            Update,
+            (
+                spawn_event_ui.run_if(resource_exists_and_changed::<ActiveEvent>),
+                handle_event_buttons.run_if(resource_exists::<ActiveEvent>),
+            )
+                .chain()
+                .run_if(in_state(AppState::Event)),
+        )
+        .add_systems(OnExit(AppState::Event), teardown_event);
+    }
+}
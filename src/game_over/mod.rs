@@ -0,0 +1,267 @@
+use bevy::prelude::*;
+
+use crate::asset::card::{CardLibrary, CardLibraryHandle};
+use crate::asset::class::{ClassLibrary, ClassLibraryHandle};
+use crate::asset::realm::{RealmLibrary, RealmLibraryHandle};
+use crate::menu::resource::Language;
+use crate::menu::{BACKDROP, NORMAL_BUTTON, TEXT};
+use crate::profile::Profile;
+use crate::run::telemetry::{self, TelemetryEvent};
+use crate::run::{RunState, save};
+use crate::state::AppState;
+
+const MOST_PLAYED: usize = 3;
+
+#[derive(Component)]
+struct OnGameOverScreen;
+
+#[derive(Component)]
+enum GameOverButtonAction {
+    MainMenu,
+    SameSeed,
+}
+
+/// Lines summarising the run, from how far it got to what it earned
+fn summary(
+    run: &RunState,
+    profile: &Profile,
+    cards: Option<&CardLibrary>,
+    classes: Option<&ClassLibrary>,
+    realms: Option<&RealmLibrary>,
+) -> Vec<String> {
+    let record = &run.record;
+    let class = classes
+        .and_then(|classes| classes.0.get(&run.class))
+        .map_or(run.class.0.clone(), |class| class.name.clone());
+    let realms_reached = record
+        .realms
+        .iter()
+        .map(|id| {
+            realms
+                .and_then(|realms| realms.0.get(id))
+                .map_or(id.0.clone(), |realm| realm.name.clone())
+        })
+        .collect::<Vec<_>>();
+    let most_played = record
+        .most_played(MOST_PLAYED)
+        .into_iter()
+        .map(|(id, times)| {
+            let name = cards
+                .and_then(|cards| cards.0.get(id))
+                .map_or(id.0.as_str(), |card| card.name.as_str());
+            format!("{} x{}", name, times)
+        })
+        .collect::<Vec<_>>();
+    let progress = profile.progress();
+    let earned = [
+        (
+            progress
+                .thread_slots
+                .saturating_sub(record.progress.thread_slots),
+            "Thread Slots",
+        ),
+        (
+            progress.unlocks.saturating_sub(record.progress.unlocks),
+            "unlocks",
+        ),
+    ]
+    .into_iter()
+    .filter(|(count, _)| *count > 0)
+    .map(|(count, what)| format!("{} {}", count, what))
+    .collect::<Vec<_>>();
+
+    let mut lines = vec![
+        format!("Class: {}", class),
+        format!("Realms reached: {}", realms_reached.join(", ")),
+        format!("Encounters won: {}", record.encounters_won),
+        format!("Damage dealt: {}", record.damage_dealt),
+    ];
+    if !most_played.is_empty() {
+        lines.push(format!("Most played: {}", most_played.join(", ")));
+    }
+    if let Some(defeated_by) = &record.defeated_by {
+        lines.push(format!("Fell to: {}", defeated_by));
+    }
+    lines.push(format!(
+        "Earned: {}",
+        if earned.is_empty() {
+            "nothing".to_string()
+        } else {
+            earned.join(", ")
+        }
+    ));
+    lines.push(format!("Seed: {}", run.rng.seed));
+    lines
+}
+
+fn setup_game_over(
+    mut commands: Commands,
+    language: Res<Language>,
+    asset_server: Res<AssetServer>,
+    run: Option<Res<RunState>>,
+    profile: Res<Profile>,
+    cards_handle: Res<CardLibraryHandle>,
+    cards: Res<Assets<CardLibrary>>,
+    classes_handle: Res<ClassLibraryHandle>,
+    classes: Res<Assets<ClassLibrary>>,
+    realms_handle: Res<RealmLibraryHandle>,
+    realms: Res<Assets<RealmLibrary>>,
+) {
+    let font = asset_server.load(language.font_path());
+    let text_font = TextFont {
+        font_size: 16.0,
+        font: font.clone(),
+        ..default()
+    };
+    let (title, lines) = match &run {
+        Some(run) => (
+            if run.record.victory {
+                "victory"
+            } else {
+                "defeat"
+            },
+            summary(
+                run,
+                &profile,
+                cards.get(cards_handle.0.id()),
+                classes.get(classes_handle.0.id()),
+                realms.get(realms_handle.0.id()),
+            ),
+        ),
+        None => ("game over", Vec::new()),
+    };
+
+    let panel = commands
+        .spawn((
+            Node {
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                padding: UiRect::all(Val::Px(12.0)),
+                ..default()
+            },
+            BackgroundColor(BACKDROP),
+            children![(
+                Text::new(title),
+                TextFont {
+                    font_size: 24.0,
+                    font,
+                    ..default()
+                },
+                TextColor(TEXT),
+            )],
+        ))
+        .id();
+    for line in lines {
+        let text = commands
+            .spawn((Text::new(line), text_font.clone(), TextColor(TEXT)))
+            .id();
+        commands.entity(panel).add_child(text);
+    }
+    let mut buttons = vec![(GameOverButtonAction::MainMenu, "main menu")];
+    if run.is_some() {
+        buttons.push((GameOverButtonAction::SameSeed, "new run, same seed"));
+    }
+    for (action, text) in buttons {
+        let button = commands
+            .spawn((
+                Button,
+                Node {
+                    min_height: Val::Px(28.0),
+                    margin: UiRect::top(Val::Px(6.0)),
+                    padding: UiRect::horizontal(Val::Px(8.0)),
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                BackgroundColor(NORMAL_BUTTON),
+                action,
+                children![(Text::new(text), text_font.clone(), TextColor(TEXT))],
+            ))
+            .id();
+        commands.entity(panel).add_child(button);
+    }
+
+    commands
+        .spawn((
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            OnGameOverScreen,
+        ))
+        .add_child(panel);
+}
+
+fn handle_game_over_buttons(
+    mut commands: Commands,
+    interaction_query: Query<
+        (&Interaction, &GameOverButtonAction),
+        (Changed<Interaction>, With<Button>),
+    >,
+    run: Option<Res<RunState>>,
+    mut profile: ResMut<Profile>,
+    classes_handle: Res<ClassLibraryHandle>,
+    classes: Res<Assets<ClassLibrary>>,
+    mut app_state: ResMut<NextState<AppState>>,
+) {
+    for (interaction, game_over_button_action) in &interaction_query {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        match game_over_button_action {
+            GameOverButtonAction::MainMenu => app_state.set(AppState::Menu),
+            GameOverButtonAction::SameSeed => {
+                let Some(run) = &run else {
+                    continue;
+                };
+                let id = run.record.class.as_ref().unwrap_or(&run.class);
+                let Some(class) = classes
+                    .get(classes_handle.0.id())
+                    .and_then(|classes| classes.0.get(id))
+                else {
+                    log::warn!("Missing class {}", id.0);
+                    continue;
+                };
+                log::info!("Restarting {} with seed {}", id.0, run.rng.seed);
+                save::delete_checkpoint();
+                let mut new_run = RunState::new(run.rng.seed, id, class);
+                profile.apply(&mut new_run);
+                telemetry::record(
+                    &new_run,
+                    TelemetryEvent::RunStarted {
+                        class: id.clone(),
+                        seed: new_run.rng.seed,
+                    },
+                );
+                commands.insert_resource(new_run);
+                app_state.set(AppState::Campaign);
+            }
+        }
+        break;
+    }
+}
+
+fn teardown_game_over(
+    mut commands: Commands,
+    game_over_items_query: Query<Entity, With<OnGameOverScreen>>,
+) {
+    for game_over_entity in &game_over_items_query {
+        commands.entity(game_over_entity).despawn();
+    }
+}
+
+#[derive(Default)]
+pub struct GameOverPlugin;
+
+impl Plugin for GameOverPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(AppState::GameOver), setup_game_over)
+            .add_systems(
+                Update,
+                handle_game_over_buttons.run_if(in_state(AppState::GameOver)),
+            )
+            .add_systems(OnExit(AppState::GameOver), teardown_game_over);
+    }
+}
@@ -0,0 +1,293 @@
+use bevy::prelude::*;
+use bevy::ui::UiSystem;
+
+use crate::cards::InteractiveNode;
+use crate::cards::event::{NodeInteraction, NodeInteractionType};
+use crate::state::{AppState, MenuState};
+
+const FOCUS: Color = Color::srgb(1.0, 0.85, 0.45);
+const FOCUS_WIDTH: f32 = 2.;
+
+/// A player intent, whether it came from the keyboard or a gamepad
+#[derive(Event, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InputAction {
+    Next,
+    Prev,
+    /// Cycle the enemy a card will be played against
+    Target,
+    Play,
+    EndTurn,
+    Inspect,
+    Back,
+}
+
+/// Which keys and gamepad buttons raise which actions
+#[derive(Resource, Clone, Debug)]
+pub struct InputMap {
+    pub keys: Vec<(KeyCode, InputAction)>,
+    pub buttons: Vec<(GamepadButton, InputAction)>,
+}
+
+impl Default for InputMap {
+    fn default() -> Self {
+        use InputAction::*;
+        Self {
+            keys: vec![
+                (KeyCode::ArrowRight, Next),
+                (KeyCode::ArrowDown, Next),
+                (KeyCode::KeyD, Next),
+                (KeyCode::KeyS, Next),
+                (KeyCode::ArrowLeft, Prev),
+                (KeyCode::ArrowUp, Prev),
+                (KeyCode::KeyA, Prev),
+                (KeyCode::KeyW, Prev),
+                (KeyCode::Tab, Target),
+                (KeyCode::KeyT, Target),
+                (KeyCode::Enter, Play),
+                (KeyCode::Space, Play),
+                (KeyCode::KeyE, EndTurn),
+                (KeyCode::KeyI, Inspect),
+                (KeyCode::Escape, Back),
+                (KeyCode::Backspace, Back),
+            ],
+            buttons: vec![
+                (GamepadButton::DPadRight, Next),
+                (GamepadButton::DPadDown, Next),
+                (GamepadButton::DPadLeft, Prev),
+                (GamepadButton::DPadUp, Prev),
+                (GamepadButton::RightTrigger, Target),
+                (GamepadButton::LeftTrigger, Target),
+                (GamepadButton::South, Play),
+                (GamepadButton::West, EndTurn),
+                (GamepadButton::North, Inspect),
+                (GamepadButton::East, Back),
+            ],
+        }
+    }
+}
+
+/// Position in the ring of focusable cards and buttons, kept by index so
+/// focus survives a hand being respawned
+#[derive(Resource, Clone, Copy, Debug, Default)]
+pub struct Focus(Option<usize>);
+
+/// The card, map location or button that keyboard and gamepad input acts on
+#[derive(Component)]
+pub struct Focused;
+
+/// The button that backs out of or leaves a screen when `Back` is pressed
+#[derive(Component)]
+pub struct BackButton;
+
+/// A button pressed by input, held for a whole frame so every system
+/// watching for the press sees it whatever its order
+#[derive(Resource, Clone, Copy, Debug, Default)]
+struct FocusPress {
+    entity: Option<Entity>,
+    held: bool,
+}
+
+fn read_input(
+    input_map: Res<InputMap>,
+    keys: Res<ButtonInput<KeyCode>>,
+    gamepads: Query<&Gamepad>,
+    mut input_actions: EventWriter<InputAction>,
+) {
+    for &(key, action) in &input_map.keys {
+        if keys.just_pressed(key) {
+            input_actions.write(action);
+        }
+    }
+    for gamepad in &gamepads {
+        for &(button, action) in &input_map.buttons {
+            if gamepad.just_pressed(button) {
+                input_actions.write(action);
+            }
+        }
+    }
+}
+
+/// Move focus through the visible cards, left to right, then the buttons,
+/// top to bottom
+fn navigate_focus(
+    mut commands: Commands,
+    mut input_actions: EventReader<InputAction>,
+    mut focus: ResMut<Focus>,
+    node_query: Query<(Entity, &GlobalTransform, &InheritedVisibility), With<InteractiveNode>>,
+    button_query: Query<(Entity, &GlobalTransform, &InheritedVisibility), With<Button>>,
+    focused_query: Query<Entity, With<Focused>>,
+) {
+    let visible =
+        |(entity, transform, visibility): (Entity, &GlobalTransform, &InheritedVisibility)| {
+            visibility
+                .get()
+                .then(|| (entity, transform.translation().truncate()))
+        };
+    let mut nodes = node_query.iter().filter_map(visible).collect::<Vec<_>>();
+    nodes.sort_by(|(_, a), (_, b)| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y)));
+    let mut buttons = button_query.iter().filter_map(visible).collect::<Vec<_>>();
+    buttons.sort_by(|(_, a), (_, b)| a.y.total_cmp(&b.y).then(a.x.total_cmp(&b.x)));
+    let ring = nodes
+        .into_iter()
+        .chain(buttons)
+        .map(|(entity, _)| entity)
+        .collect::<Vec<_>>();
+
+    for action in input_actions.read() {
+        let len = ring.len();
+        focus.0 = match (action, focus.0) {
+            _ if len == 0 => None,
+            (InputAction::Next, Some(i)) => Some((i + 1) % len),
+            (InputAction::Prev, Some(i)) => Some((i + len - 1) % len),
+            (InputAction::Next | InputAction::Prev, None) => Some(0),
+            (InputAction::Back, _) => None,
+            (_, focused) => focused,
+        };
+    }
+
+    let target = focus
+        .0
+        .and_then(|i| ring.get(i.min(ring.len().saturating_sub(1))));
+    for entity in &focused_query {
+        if Some(&entity) != target {
+            commands.entity(entity).remove::<Focused>();
+        }
+    }
+    if let Some(&entity) = target
+        && !focused_query.contains(entity)
+    {
+        commands.entity(entity).insert(Focused);
+    }
+}
+
+/// Press the focused button or click the focused card, as the mouse would,
+/// or the screen's back button whatever has focus
+fn activate_focus(
+    mut input_actions: EventReader<InputAction>,
+    mut button_query: Query<&mut Interaction, With<Button>>,
+    focused_query: Query<Entity, With<Focused>>,
+    back_query: Query<(Entity, &InheritedVisibility), With<BackButton>>,
+    mut node_interaction_events: EventWriter<NodeInteraction>,
+    mut focus_press: ResMut<FocusPress>,
+) {
+    for action in input_actions.read() {
+        if *action == InputAction::Back {
+            if let Some((entity, _)) = back_query.iter().find(|(_, visibility)| visibility.get())
+                && let Ok(mut interaction) = button_query.get_mut(entity)
+            {
+                *interaction = Interaction::Pressed;
+                *focus_press = FocusPress {
+                    entity: Some(entity),
+                    held: false,
+                };
+            }
+            continue;
+        }
+        let Ok(entity) = focused_query.single() else {
+            continue;
+        };
+        let interaction_type = match action {
+            InputAction::Play => NodeInteractionType::LeftClick,
+            InputAction::Inspect => NodeInteractionType::RightClick,
+            _ => continue,
+        };
+        match button_query.get_mut(entity) {
+            Ok(mut interaction) if interaction_type == NodeInteractionType::LeftClick => {
+                *interaction = Interaction::Pressed;
+                *focus_press = FocusPress {
+                    entity: Some(entity),
+                    held: false,
+                };
+            }
+            Ok(_) => {}
+            Err(_) => {
+                node_interaction_events.write(NodeInteraction {
+                    entity,
+                    interaction_type,
+                });
+            }
+        }
+    }
+}
+
+fn release_focus_press(
+    mut focus_press: ResMut<FocusPress>,
+    mut button_query: Query<&mut Interaction, With<Button>>,
+) {
+    let Some(entity) = focus_press.entity else {
+        return;
+    };
+    if !focus_press.held {
+        focus_press.held = true;
+        return;
+    }
+    if let Ok(mut interaction) = button_query.get_mut(entity) {
+        interaction.set_if_neq(Interaction::None);
+    }
+    *focus_press = FocusPress::default();
+}
+
+/// A new screen starts with nothing focused
+fn reset_focus(mut focus: ResMut<Focus>) {
+    focus.0 = None;
+}
+
+fn highlight_focused_buttons(
+    mut commands: Commands,
+    focused_query: Query<Entity, (Added<Focused>, With<Button>)>,
+    mut unfocused: RemovedComponents<Focused>,
+) {
+    for entity in unfocused.read() {
+        if let Ok(mut entity_commands) = commands.get_entity(entity) {
+            entity_commands.remove::<Outline>();
+        }
+    }
+    for entity in &focused_query {
+        commands
+            .entity(entity)
+            .insert(Outline::new(Val::Px(FOCUS_WIDTH), Val::ZERO, FOCUS));
+    }
+}
+
+fn highlight_focused_nodes(
+    mut gizmos: Gizmos,
+    images: Res<Assets<Image>>,
+    focused_query: Query<(&Sprite, &GlobalTransform), (With<Focused>, With<InteractiveNode>)>,
+) {
+    for (sprite, transform) in &focused_query {
+        let size = sprite
+            .custom_size
+            .or_else(|| images.get(&sprite.image).map(Image::size_f32))
+            .unwrap_or_default();
+        let (scale, _, translation) = transform.to_scale_rotation_translation();
+        gizmos.rect_2d(
+            Isometry2d::from_translation(translation.truncate()),
+            size * scale.truncate() + FOCUS_WIDTH,
+            FOCUS,
+        );
+    }
+}
+
+#[derive(Default)]
+pub struct InputPlugin;
+
+impl Plugin for InputPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<InputAction>()
+            .init_resource::<InputMap>()
+            .init_resource::<Focus>()
+            .init_resource::<FocusPress>()
+            .add_systems(
+                PreUpdate,
+                (read_input, navigate_focus, activate_focus)
+                    .chain()
+                    .after(UiSystem::Focus),
+            )
+            .add_systems(Update, (highlight_focused_buttons, highlight_focused_nodes))
+            .add_systems(
+                Update,
+                reset_focus.run_if(state_changed::<AppState>.or(state_changed::<MenuState>)),
+            )
+            .add_systems(Last, release_focus_press);
+    }
+}
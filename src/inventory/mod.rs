@@ -0,0 +1,152 @@
+use bevy::prelude::*;
+
+use crate::asset::artefact::{ArtefactId, ArtefactLibrary, ArtefactLibraryHandle};
+use crate::battle::combat::Battle;
+use crate::menu::resource::Language;
+use crate::menu::{BACKDROP, TEXT};
+use crate::run::RunState;
+use crate::state::AppState;
+
+const ICON_SIZE: f32 = 20.;
+const DISABLED: Color = Color::srgba(0.3, 0.3, 0.3, 0.8);
+
+#[derive(Component)]
+struct InventoryBar;
+
+#[derive(Component)]
+struct InventorySlot(ArtefactId);
+
+#[derive(Component)]
+struct InventoryTooltip;
+
+/// Rebuild the bar of carried artefacts whenever the run or battle changes,
+/// greying out any an enemy has disabled
+fn sync_inventory_bar(
+    mut commands: Commands,
+    language: Res<Language>,
+    asset_server: Res<AssetServer>,
+    run: Res<RunState>,
+    battle: Option<Res<Battle>>,
+    artefacts_handle: Res<ArtefactLibraryHandle>,
+    artefacts: Res<Assets<ArtefactLibrary>>,
+    bar_query: Query<Entity, With<InventoryBar>>,
+) {
+    let battle_changed = battle.as_ref().is_some_and(|battle| battle.is_changed());
+    if !run.is_changed() && !battle_changed && !bar_query.is_empty() {
+        return;
+    }
+    for bar_entity in &bar_query {
+        commands.entity(bar_entity).despawn();
+    }
+    let Some(artefacts) = artefacts.get(artefacts_handle.0.id()) else {
+        return;
+    };
+    let held = match &battle {
+        Some(battle) => battle
+            .artefacts
+            .keys()
+            .map(|id| (id.clone(), battle.disabled.contains(id)))
+            .collect::<Vec<_>>(),
+        None => run.inventory.iter().map(|id| (id.clone(), false)).collect(),
+    };
+
+    let slots = commands
+        .spawn(Node {
+            column_gap: Val::Px(4.0),
+            ..default()
+        })
+        .id();
+    for (id, disabled) in held {
+        let Some(artefact) = artefacts.0.get(&id) else {
+            continue;
+        };
+        let mut icon = ImageNode::new(asset_server.load(artefact.image.clone()));
+        if disabled {
+            icon.color = DISABLED;
+        }
+        let slot = commands
+            .spawn((
+                Node {
+                    width: Val::Px(ICON_SIZE),
+                    height: Val::Px(ICON_SIZE),
+                    ..default()
+                },
+                icon,
+                Interaction::default(),
+                InventorySlot(id),
+            ))
+            .id();
+        commands.entity(slots).add_child(slot);
+    }
+
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                top: Val::Px(4.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            InventoryBar,
+        ))
+        .add_child(slots)
+        .with_children(|parent| {
+            parent.spawn((
+                Text::default(),
+                TextFont {
+                    font_size: 12.0,
+                    font: asset_server.load(language.font_path()),
+                    ..default()
+                },
+                TextColor(TEXT),
+                BackgroundColor(BACKDROP),
+                InventoryTooltip,
+            ));
+        });
+}
+
+fn handle_inventory_hover(
+    slot_query: Query<(&Interaction, &InventorySlot), Changed<Interaction>>,
+    mut tooltip_query: Query<&mut Text, With<InventoryTooltip>>,
+    artefacts_handle: Res<ArtefactLibraryHandle>,
+    artefacts: Res<Assets<ArtefactLibrary>>,
+) {
+    for (interaction, InventorySlot(id)) in &slot_query {
+        let tooltip = match interaction {
+            Interaction::None => String::new(),
+            _ => artefacts
+                .get(artefacts_handle.0.id())
+                .and_then(|artefacts| artefacts.0.get(id))
+                .map(|artefact| format!("{}: {}", artefact.name, artefact.description))
+                .unwrap_or_default(),
+        };
+        for mut text in &mut tooltip_query {
+            text.0 = tooltip.clone();
+        }
+    }
+}
+
+fn teardown_inventory_bar(mut commands: Commands, bar_query: Query<Entity, With<InventoryBar>>) {
+    for bar_entity in &bar_query {
+        commands.entity(bar_entity).despawn();
+    }
+}
+
+#[derive(Default)]
+pub struct InventoryPlugin;
+
+impl Plugin for InventoryPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (sync_inventory_bar, handle_inventory_hover)
+                .chain()
+                .run_if(resource_exists::<RunState>)
+                .run_if(in_state(AppState::Campaign).or(in_state(AppState::Battle))),
+        )
+        .add_systems(OnExit(AppState::Campaign), teardown_inventory_bar)
+        .add_systems(OnExit(AppState::Battle), teardown_inventory_bar);
+    }
+}
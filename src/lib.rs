@@ -5,8 +5,22 @@ pub mod asset;
 pub mod battle;
 pub mod campaign;
 pub mod cards;
+pub mod deck;
+pub mod dialogue;
+pub mod event;
+pub mod game_over;
+pub mod input;
+pub mod inventory;
+pub mod logging;
 pub mod menu;
+pub mod profile;
+pub mod replay;
+pub mod rest;
+pub mod reward;
+pub mod run;
+pub mod shop;
 pub mod state;
+pub mod wellspring;
 pub mod zindex;
 
 use bevy::prelude::*;
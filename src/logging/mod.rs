@@ -0,0 +1,22 @@
+use std::path::Path;
+
+const LOG_CONFIG: &str = "assets/log4rs.yaml";
+
+/// Route `log` records through log4rs when its config can be loaded,
+/// returning whether it took the global logger from Bevy's LogPlugin
+pub fn init() -> bool {
+    if !Path::new(LOG_CONFIG).exists() {
+        return false;
+    }
+    match log4rs::init_file(LOG_CONFIG, Default::default()) {
+        Ok(()) => {
+            log::info!(config = LOG_CONFIG; "Logging configured from {}", LOG_CONFIG);
+            true
+        }
+        Err(err) => {
+            // Nothing is listening for log records yet
+            eprintln!("Failed to load {}: {}", LOG_CONFIG, err);
+            false
+        }
+    }
+}
@@ -1,3 +1,4 @@
+use bevy::log::LogPlugin;
 use bevy::prelude::*;
 use bevy_pixcam::PixelCameraPlugin;
 use bevy_scriptum::prelude::*;
@@ -5,27 +6,48 @@ use bevy_scriptum::runtimes::lua::prelude::*;
 
 use pipedream::{
     asset::AssetDataPlugin, battle::BattlePlugin, campaign::CampaignPlugin,
-    cards::InteractiveCardsPlugin, menu::MenuUiPlugin, setup_app, state::StatesPlugin,
+    cards::InteractiveCardsPlugin, deck::DeckPlugin, dialogue::DialoguePlugin, event::EventPlugin,
+    game_over::GameOverPlugin, input::InputPlugin, inventory::InventoryPlugin, logging,
+    menu::MenuUiPlugin, profile::ProfilePlugin, replay::ReplayPlugin, rest::RestPlugin,
+    reward::RewardPlugin, setup_app, shop::ShopPlugin, state::StatesPlugin,
+    wellspring::WellspringPlugin,
 };
 
 fn main() {
+    let mut default_plugins = DefaultPlugins.set(ImagePlugin::default_nearest());
+    // Only one global logger may be set, and log4rs claims it first
+    if logging::init() {
+        default_plugins = default_plugins.disable::<LogPlugin>();
+    }
+
     let mut app = App::new();
-    app.add_plugins((
-        DefaultPlugins.set(ImagePlugin::default_nearest()),
-        PixelCameraPlugin,
-    ))
-    .add_scripting::<LuaRuntime>(|_| {
-        // instantiated through ::add_scripting_api
-    })
-    .add_plugins((
-        AssetDataPlugin,
-        BattlePlugin,
-        CampaignPlugin,
-        InteractiveCardsPlugin,
-        MenuUiPlugin,
-        StatesPlugin,
-    ))
-    .add_systems(Startup, setup_app);
+    app.add_plugins((default_plugins, PixelCameraPlugin))
+        .add_scripting::<LuaRuntime>(|_| {
+            // instantiated through ::add_scripting_api
+        })
+        .add_plugins((
+            AssetDataPlugin,
+            InputPlugin,
+            InteractiveCardsPlugin,
+            MenuUiPlugin,
+            ProfilePlugin,
+            ReplayPlugin,
+            StatesPlugin,
+        ))
+        .add_plugins((
+            BattlePlugin,
+            CampaignPlugin,
+            DeckPlugin,
+            DialoguePlugin,
+            EventPlugin,
+            GameOverPlugin,
+            InventoryPlugin,
+            RestPlugin,
+            RewardPlugin,
+            ShopPlugin,
+            WellspringPlugin,
+        ))
+        .add_systems(Startup, setup_app);
 
     #[cfg(feature = "dev_mode")]
     {
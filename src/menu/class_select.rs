@@ -0,0 +1,170 @@
+use bevy::prelude::*;
+
+use crate::{
+    asset::{
+        actor::{ActorLibrary, ActorLibraryHandle},
+        class::{ClassLibrary, ClassLibraryHandle},
+    },
+    input::BackButton,
+    menu::{BACKDROP, MenuButtonAction, NORMAL_BUTTON, TEXT, resource::Language},
+    state::MenuState,
+};
+
+const PORTRAIT_SIZE: f32 = 64.0;
+
+#[derive(Component)]
+pub struct OnClassSelectScreen;
+
+fn setup_class_select(
+    mut commands: Commands,
+    language: Res<Language>,
+    asset_server: Res<AssetServer>,
+    classes_handle: Res<ClassLibraryHandle>,
+    classes: Res<Assets<ClassLibrary>>,
+    actors_handle: Res<ActorLibraryHandle>,
+    actors: Res<Assets<ActorLibrary>>,
+) {
+    let font = asset_server.load(language.font_path());
+    let background = asset_server.load("backgrounds/mountains/mountains4.png");
+
+    let class_node = Node {
+        width: Val::Px(180.0),
+        margin: UiRect::all(Val::Px(8.0)),
+        padding: UiRect::all(Val::Px(8.0)),
+        flex_direction: FlexDirection::Column,
+        align_items: AlignItems::Center,
+        ..default()
+    };
+    let title_font = TextFont {
+        font_size: 24.0,
+        font: font.clone(),
+        ..default()
+    };
+    let description_font = TextFont {
+        font_size: 14.0,
+        font: font.clone(),
+        ..default()
+    };
+    let button_text_font = TextFont {
+        font_size: 33.0,
+        font: font.clone(),
+        ..default()
+    };
+
+    let grid = commands
+        .spawn(Node {
+            flex_wrap: FlexWrap::Wrap,
+            justify_content: JustifyContent::Center,
+            max_width: Val::Percent(90.0),
+            ..default()
+        })
+        .id();
+    let Some(classes) = classes.get(classes_handle.0.id()) else {
+        log::warn!("Missing class library while spawning class select");
+        return;
+    };
+    for (id, class) in classes.0.iter().filter(|(_, class)| !class.is_prestige) {
+        let portrait = actors
+            .get(actors_handle.0.id())
+            .and_then(|actors| actors.0.get(&class.portrait))
+            .map(|actor| asset_server.load(actor.face_image.clone()))
+            .unwrap_or_default();
+        let button = commands
+            .spawn((
+                Button,
+                class_node.clone(),
+                BackgroundColor(NORMAL_BUTTON),
+                MenuButtonAction::ChooseClass(id.clone()),
+                children![
+                    (
+                        Node {
+                            width: Val::Px(PORTRAIT_SIZE),
+                            height: Val::Px(PORTRAIT_SIZE),
+                            ..default()
+                        },
+                        ImageNode::new(portrait),
+                    ),
+                    (
+                        Text::new(class.name.clone()),
+                        title_font.clone(),
+                        TextColor(TEXT)
+                    ),
+                    (
+                        Text::new(class.description.clone()),
+                        description_font.clone(),
+                        TextColor(TEXT),
+                    ),
+                ],
+            ))
+            .id();
+        commands.entity(grid).add_child(button);
+    }
+
+    commands
+        .spawn((
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            OnClassSelectScreen,
+            super::OnMenuScreen,
+            ImageNode::new(background),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new(" choose your path "),
+                TextFont {
+                    font_size: 45.0,
+                    font,
+                    ..default()
+                },
+                TextColor(TEXT),
+                BackgroundColor(BACKDROP),
+                Node {
+                    margin: UiRect::all(Val::Px(20.0)),
+                    ..default()
+                },
+            ));
+        })
+        .add_child(grid)
+        .with_children(|parent| {
+            parent.spawn((
+                Button,
+                Node {
+                    width: Val::Px(300.0),
+                    height: Val::Px(65.0),
+                    margin: UiRect::all(Val::Px(20.0)),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                BackgroundColor(NORMAL_BUTTON),
+                MenuButtonAction::MainMenu,
+                BackButton,
+                children![(Text::new("back"), button_text_font, TextColor(TEXT),),],
+            ));
+        });
+}
+
+pub fn teardown_class_select(
+    mut commands: Commands,
+    class_select_items_query: Query<Entity, With<OnClassSelectScreen>>,
+) {
+    for menu_entity in &class_select_items_query {
+        commands.entity(menu_entity).despawn();
+    }
+}
+
+#[derive(Default)]
+pub struct ClassSelectUiPlugin;
+
+impl Plugin for ClassSelectUiPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(MenuState::ClassSelect), setup_class_select)
+            .add_systems(OnExit(MenuState::ClassSelect), teardown_class_select);
+    }
+}
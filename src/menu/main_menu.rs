@@ -2,6 +2,7 @@ use bevy::prelude::*;
 
 use crate::{
     menu::{BACKDROP, MenuButtonAction, NORMAL_BUTTON, TEXT, resource::Language},
+    run::save,
     state::MenuState,
 };
 
@@ -31,18 +32,8 @@ fn setup_main_menu(
         ..default()
     };
 
-    commands.spawn((
-        Node {
-            width: Val::Percent(100.0),
-            height: Val::Percent(100.0),
-            align_items: AlignItems::Center,
-            justify_content: JustifyContent::Center,
-            ..default()
-        },
-        OnMainMenuScreen,
-        super::OnMenuScreen,
-        ImageNode::new(background),
-        children![(
+    let buttons = commands
+        .spawn((
             Node {
                 flex_direction: FlexDirection::Column,
                 align_items: AlignItems::Center,
@@ -87,14 +78,53 @@ fn setup_main_menu(
                 ),
                 (
                     Button,
-                    button_node,
+                    button_node.clone(),
                     BackgroundColor(NORMAL_BUTTON),
                     MenuButtonAction::Quit,
-                    children![(Text::new("quit"), button_text_font, TextColor(TEXT),),]
+                    children![(Text::new("quit"), button_text_font.clone(), TextColor(TEXT),),]
                 ),
-            ]
-        )],
-    ));
+            ],
+        ))
+        .id();
+
+    // Continue sits above new game, but only when there is a run to continue,
+    // or a lost run that may resume from its last Wellspring
+    let resume = if save::has_save() {
+        Some((MenuButtonAction::Continue, "continue"))
+    } else if save::has_checkpoint() {
+        Some((MenuButtonAction::Resume, "resume from wellspring"))
+    } else {
+        None
+    };
+    if let Some((action, text)) = resume {
+        let continue_button = commands
+            .spawn((
+                Button,
+                button_node,
+                BackgroundColor(NORMAL_BUTTON),
+                action,
+                children![(Text::new(text), button_text_font, TextColor(TEXT),),],
+            ))
+            .id();
+        commands
+            .entity(buttons)
+            .insert_children(1, &[continue_button]);
+    }
+
+    commands
+        .spawn((
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            OnMainMenuScreen,
+            super::OnMenuScreen,
+            ImageNode::new(background),
+        ))
+        .add_child(buttons);
 }
 
 pub fn teardown_main_menu(
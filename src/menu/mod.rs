@@ -1,12 +1,19 @@
 use bevy::prelude::*;
 
 use crate::{
-    menu::resource::ResourcesPlugin,
+    asset::class::{ClassId, ClassLibrary, ClassLibraryHandle},
+    menu::resource::{ResourcesPlugin, Settings},
+    profile::Profile,
+    run::{
+        RunState, save,
+        telemetry::{self, TelemetryEvent},
+    },
     state::{AppState, MenuState},
 };
 
+mod class_select;
 mod main_menu;
-mod resource;
+pub mod resource;
 mod settings;
 
 #[derive(Component)]
@@ -15,19 +22,23 @@ struct OnMenuScreen;
 #[derive(Component)]
 enum MenuButtonAction {
     Play,
+    Continue,
+    Resume,
+    ChooseClass(ClassId),
     MainMenu,
     Settings,
+    ToggleUndo,
     Quit,
 }
 #[derive(Component)]
 struct SelectedButton;
 
-const TEXT: Color = Color::srgb(0.9, 0.9, 0.9);
-const NORMAL_BUTTON: Color = Color::srgba(0.15, 0.15, 0.15, 0.9);
+pub(crate) const TEXT: Color = Color::srgb(0.9, 0.9, 0.9);
+pub(crate) const NORMAL_BUTTON: Color = Color::srgba(0.15, 0.15, 0.15, 0.9);
 const HOVERED_BUTTON: Color = Color::srgba(0.25, 0.25, 0.25, 0.9);
 const HOVERED_PRESSED_BUTTON: Color = Color::srgba(0.25, 0.65, 0.25, 0.9);
 const PRESSED_BUTTON: Color = Color::srgba(0.35, 0.75, 0.35, 0.9);
-const BACKDROP: Color = Color::srgba(0.0, 0.0, 0.0, 0.9);
+pub(crate) const BACKDROP: Color = Color::srgba(0.0, 0.0, 0.0, 0.9);
 
 fn handle_button_interaction(
     mut interaction_query: Query<
@@ -50,15 +61,55 @@ fn handle_menu_action(
         (&Interaction, &MenuButtonAction),
         (Changed<Interaction>, With<Button>),
     >,
+    mut commands: Commands,
     mut app_exit_events: EventWriter<AppExit>,
     mut app_state: ResMut<NextState<AppState>>,
     mut menu_state: ResMut<NextState<MenuState>>,
+    mut profile: ResMut<Profile>,
+    mut settings: ResMut<Settings>,
+    classes_handle: Res<ClassLibraryHandle>,
+    classes: Res<Assets<ClassLibrary>>,
 ) {
     for (interaction, menu_button_action) in &interaction_query {
         if *interaction == Interaction::Pressed {
             match menu_button_action {
                 MenuButtonAction::Play => {
-                    app_state.set(AppState::Battle);
+                    menu_state.set(MenuState::ClassSelect);
+                }
+                MenuButtonAction::ChooseClass(id) => {
+                    let Some(class) = classes
+                        .get(classes_handle.0.id())
+                        .and_then(|classes| classes.0.get(id))
+                    else {
+                        log::warn!("Missing class {}", id.0);
+                        continue;
+                    };
+                    // A new run abandons any Wellspring checkpoint of the last
+                    save::delete_checkpoint();
+                    let mut run = RunState::new(rand::random(), id, class);
+                    profile.apply(&mut run);
+                    telemetry::record(
+                        &run,
+                        TelemetryEvent::RunStarted {
+                            class: id.clone(),
+                            seed: run.rng.seed,
+                        },
+                    );
+                    commands.insert_resource(run);
+                    app_state.set(AppState::Campaign);
+                }
+                MenuButtonAction::Continue => {
+                    if let Some(run) = save::load() {
+                        commands.insert_resource(run);
+                        app_state.set(AppState::Campaign);
+                    }
+                }
+                MenuButtonAction::Resume => {
+                    if let Some(run) = save::resume_checkpoint() {
+                        save::save(&run);
+                        commands.insert_resource(run);
+                        app_state.set(AppState::Campaign);
+                    }
                 }
                 MenuButtonAction::MainMenu => {
                     menu_state.set(MenuState::MainMenu);
@@ -66,6 +117,9 @@ fn handle_menu_action(
                 MenuButtonAction::Settings => {
                     menu_state.set(MenuState::Settings);
                 }
+                MenuButtonAction::ToggleUndo => {
+                    settings.undo = !settings.undo;
+                }
                 MenuButtonAction::Quit => {
                     app_exit_events.write(AppExit::Success);
                 }
@@ -97,7 +151,11 @@ impl Plugin for MenuUiPlugin {
         app.init_state::<MenuState>()
             .add_plugins(ResourcesPlugin)
             .add_systems(OnEnter(AppState::Menu), setup_menu)
-            .add_plugins((main_menu::MainMenuUiPlugin, settings::SettingsMenuUiPlugin))
+            .add_plugins((
+                main_menu::MainMenuUiPlugin,
+                class_select::ClassSelectUiPlugin,
+                settings::SettingsMenuUiPlugin,
+            ))
             .add_systems(PreUpdate, (handle_button_interaction, handle_menu_action))
             .add_systems(OnExit(AppState::Menu), teardown_menu);
     }
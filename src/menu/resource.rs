@@ -1,4 +1,11 @@
+use std::fs;
+
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::run::save::{data_path, write_atomic};
+
+const SETTINGS_FILE: &str = "settings.json";
 
 #[derive(Resource, Component, Default)]
 pub enum Language {
@@ -13,6 +20,46 @@ impl Language {
     }
 }
 
+/// Player preferences, kept apart from the profile and the run
+#[derive(Resource, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct Settings {
+    /// Allow taking back a card play that revealed nothing hidden
+    #[serde(default)]
+    pub undo: bool,
+}
+
+fn load_settings(mut commands: Commands) {
+    let settings = data_path(SETTINGS_FILE)
+        .filter(|path| path.exists())
+        .and_then(|path| {
+            fs::read_to_string(&path)
+                .inspect_err(|err| {
+                    log::warn!("Failed to read settings {}: {}", path.display(), err)
+                })
+                .ok()
+        })
+        .and_then(|json| {
+            serde_json::from_str::<Settings>(&json)
+                .inspect_err(|err| log::warn!("Failed to parse settings: {}", err))
+                .ok()
+        })
+        .unwrap_or_default();
+    commands.insert_resource(settings);
+}
+
+fn save_settings(settings: Res<Settings>) {
+    let Some(path) = data_path(SETTINGS_FILE) else {
+        log::warn!("No data directory to save settings into");
+        return;
+    };
+    let written = serde_json::to_string_pretty(&*settings)
+        .map_err(std::io::Error::other)
+        .and_then(|json| write_atomic(&path, &json));
+    if let Err(err) = written {
+        log::warn!("Failed to write settings {}: {}", path.display(), err);
+    }
+}
+
 fn load_fonts(language: Res<Language>, asset_server: Res<AssetServer>) {
     let _font: Handle<Font> = asset_server.load(language.font_path());
 }
@@ -23,6 +70,10 @@ pub struct ResourcesPlugin;
 impl Plugin for ResourcesPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<Language>()
-            .add_systems(Startup, load_fonts);
+            .add_systems(Startup, (load_fonts, load_settings))
+            .add_systems(
+                Last,
+                save_settings.run_if(resource_exists_and_changed::<Settings>),
+            );
     }
 }
@@ -1,17 +1,29 @@
 use bevy::prelude::*;
 
 use crate::{
-    menu::{BACKDROP, MenuButtonAction, NORMAL_BUTTON, TEXT, resource::Language},
+    input::BackButton,
+    menu::{
+        BACKDROP, MenuButtonAction, NORMAL_BUTTON, TEXT,
+        resource::{Language, Settings},
+    },
     state::MenuState,
 };
 
 #[derive(Component)]
 pub struct OnSettingsMenuScreen;
 
+#[derive(Component)]
+struct UndoSettingText;
+
+fn undo_label(settings: &Settings) -> String {
+    format!("undo: {}", if settings.undo { "on" } else { "off" })
+}
+
 fn setup_settings_menu(
     mut commands: Commands,
     language: Res<Language>,
     asset_server: Res<AssetServer>,
+    settings: Res<Settings>,
 ) {
     let font = asset_server.load(language.font_path());
     let background = asset_server.load("backgrounds/mountain/Mountains4/Bright/mountains4.png");
@@ -63,11 +75,24 @@ fn setup_settings_menu(
                         ..default()
                     },
                 ),
+                (
+                    Button,
+                    button_node.clone(),
+                    BackgroundColor(NORMAL_BUTTON),
+                    MenuButtonAction::ToggleUndo,
+                    children![(
+                        Text::new(undo_label(&settings)),
+                        button_text_font.clone(),
+                        TextColor(TEXT),
+                        UndoSettingText,
+                    )]
+                ),
                 (
                     Button,
                     button_node,
                     BackgroundColor(NORMAL_BUTTON),
                     MenuButtonAction::MainMenu,
+                    BackButton,
                     children![(Text::new("back"), button_text_font, TextColor(TEXT),),]
                 ),
             ]
@@ -75,6 +100,15 @@ fn setup_settings_menu(
     ));
 }
 
+fn sync_settings_text(
+    settings: Res<Settings>,
+    mut undo_text_query: Query<&mut Text, With<UndoSettingText>>,
+) {
+    for mut text in &mut undo_text_query {
+        text.0 = undo_label(&settings);
+    }
+}
+
 pub fn teardown_settings_menu(
     mut commands: Commands,
     settings_items_query: Query<Entity, With<OnSettingsMenuScreen>>,
@@ -90,6 +124,12 @@ pub struct SettingsMenuUiPlugin;
 impl Plugin for SettingsMenuUiPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(OnEnter(MenuState::Settings), setup_settings_menu)
+            .add_systems(
+                Update,
+                sync_settings_text.run_if(
+                    in_state(MenuState::Settings).and(resource_exists_and_changed::<Settings>),
+                ),
+            )
             .add_systems(OnExit(MenuState::Settings), teardown_settings_menu);
     }
 }
@@ -0,0 +1,168 @@
+use std::collections::BTreeSet;
+use std::fs;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::asset::card::CardId;
+use crate::asset::realm::RealmId;
+use crate::run::RunState;
+use crate::run::save::{data_path, write_atomic};
+
+const PROFILE_FILE: &str = "profile.json";
+/// Percentage added to the weight of rare card rewards per Wellspring reached
+const RARE_WEIGHT_PER_WELLSPRING: u32 = 10;
+/// Most unlocked cards added to a starting deck, however many are earned
+const MAX_STARTING_CARDS: usize = 3;
+
+/// A permanent unlock, earned once and applied to every later run
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Unlock {
+    /// An extra card shuffled into every starting deck
+    StartingCard(CardId),
+    /// A Fate Thread carried from the start, if there is a free Thread Slot
+    StartingThread(CardId),
+}
+
+/// Meta progress kept across runs, stored apart from the run save so
+/// abandoning or losing a run never loses it
+#[derive(Resource, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct Profile {
+    #[serde(default)]
+    pub thread_slots: usize,
+    #[serde(default)]
+    pub unlocks: BTreeSet<Unlock>,
+    #[serde(default)]
+    pub wellsprings_reached: BTreeSet<RealmId>,
+    #[serde(default)]
+    pub gullveig_forms_survived: BTreeSet<String>,
+    #[serde(default)]
+    pub runs_started: u32,
+}
+
+/// Counts of what the profile has earned, compared across a run to show
+/// the progress it made
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Progress {
+    pub thread_slots: usize,
+    pub unlocks: usize,
+}
+
+impl Profile {
+    pub fn progress(&self) -> Progress {
+        Progress {
+            thread_slots: self.thread_slots,
+            unlocks: self.unlocks.len(),
+        }
+    }
+
+    pub fn unlock(&mut self, unlock: Unlock) {
+        if self.unlocks.insert(unlock.clone()) {
+            log::info!("Unlocked {:?}", unlock);
+        }
+    }
+
+    /// Apply permanent unlocks to a freshly started run, with every
+    /// earned Thread Slot added to the run's own and at most
+    /// `MAX_STARTING_CARDS` unlocked cards
+    pub fn apply(&mut self, run: &mut RunState) {
+        self.runs_started += 1;
+        run.record.progress = self.progress();
+        run.thread_slots += self.thread_slots;
+        run.rare_weight += self.wellsprings_reached.len() as u32 * RARE_WEIGHT_PER_WELLSPRING;
+        let (mut cards, mut threads) = (0, 0);
+        for unlock in &self.unlocks {
+            match unlock {
+                Unlock::StartingCard(card) if cards < MAX_STARTING_CARDS => {
+                    run.deck.push(card.clone().into());
+                    cards += 1;
+                }
+                Unlock::StartingCard(_) => {}
+                Unlock::StartingThread(card) if threads < run.thread_slots => {
+                    run.deck.push(card.clone().into());
+                    threads += 1;
+                }
+                Unlock::StartingThread(_) => {}
+            }
+        }
+    }
+}
+
+fn load_profile(mut commands: Commands) {
+    let profile = data_path(PROFILE_FILE)
+        .filter(|path| path.exists())
+        .and_then(|path| {
+            fs::read_to_string(&path)
+                .inspect_err(|err| log::warn!("Failed to read profile {}: {}", path.display(), err))
+                .ok()
+        })
+        .and_then(|json| {
+            serde_json::from_str::<Profile>(&json)
+                .inspect_err(|err| log::warn!("Failed to parse profile: {}", err))
+                .ok()
+        })
+        .unwrap_or_default();
+    commands.insert_resource(profile);
+}
+
+fn save_profile(profile: Res<Profile>) {
+    let Some(path) = data_path(PROFILE_FILE) else {
+        log::warn!("No data directory to save profile into");
+        return;
+    };
+    let written = serde_json::to_string_pretty(&*profile)
+        .map_err(std::io::Error::other)
+        .and_then(|json| write_atomic(&path, &json));
+    if let Err(err) = written {
+        log::warn!("Failed to write profile {}: {}", path.display(), err);
+    }
+}
+
+#[derive(Default)]
+pub struct ProfilePlugin;
+
+impl Plugin for ProfilePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, load_profile).add_systems(
+            Last,
+            save_profile.run_if(resource_exists_and_changed::<Profile>),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::asset::class::{ClassId, ClassLibrary};
+
+    fn new_run() -> RunState {
+        let classes: ClassLibrary =
+            serde_json::from_str(include_str!("../../assets/data/classes.json")).unwrap();
+        let id = ClassId("berserker".to_string());
+        RunState::new(0, &id, &classes.0[&id])
+    }
+
+    #[test]
+    fn apply_caps_starting_cards() {
+        let mut profile = Profile::default();
+        for card in ["punch", "kick", "appeal", "taunt", "arrow"] {
+            profile.unlock(Unlock::StartingCard(CardId(card.to_string())));
+        }
+        let mut run = new_run();
+        let deck = run.deck.len();
+        profile.apply(&mut run);
+        assert_eq!(run.deck.len(), deck + MAX_STARTING_CARDS);
+    }
+
+    #[test]
+    fn apply_holds_starting_threads_to_slots() {
+        let mut profile = Profile::default();
+        for card in ["red_thread", "silver_thread", "black_thread"] {
+            profile.unlock(Unlock::StartingThread(CardId(card.to_string())));
+        }
+        let mut run = new_run();
+        let deck = run.deck.len();
+        profile.apply(&mut run);
+        assert_eq!(run.deck.len(), deck + run.thread_slots);
+    }
+}
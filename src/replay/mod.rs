@@ -0,0 +1,374 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use bevy::prelude::*;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::asset::actor::{ActorLibrary, ActorLibraryHandle};
+use crate::asset::artefact::{ArtefactLibrary, ArtefactLibraryHandle};
+use crate::asset::card::{CardLibrary, CardLibraryHandle};
+use crate::asset::class::{ClassLibrary, ClassLibraryHandle};
+use crate::battle::BattleAction;
+use crate::battle::combat::Battle;
+use crate::run::RunState;
+use crate::run::save::{self, SAVE_VERSION, data_path};
+use crate::state::{AppState, BattleState};
+
+pub const REPLAY_DIR: &str = "replays";
+/// Replays kept on disk, the oldest deleted as new battles are recorded
+const MAX_REPLAYS: usize = 20;
+
+/// Seconds between inputs at normal speed
+const STEP_SECONDS: f32 = 0.75;
+const MIN_SPEED: f32 = 0.25;
+const MAX_SPEED: f32 = 16.;
+
+/// A player input and the hash of the battle once it had resolved
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ReplayStep {
+    pub action: BattleAction,
+    pub hash: u64,
+}
+
+/// Everything needed to play a battle back: the run as it entered the battle,
+/// whose seed decides every draw, and each input made during it
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Replay {
+    pub version: u64,
+    pub recorded_at: DateTime<Utc>,
+    pub run: RunState,
+    pub steps: Vec<ReplayStep>,
+}
+
+impl Replay {
+    pub fn load(path: &Path) -> Option<Self> {
+        let json = fs::read_to_string(path)
+            .inspect_err(|err| log::warn!("Failed to read replay {}: {}", path.display(), err))
+            .ok()?;
+        let replay = serde_json::from_str::<Self>(&json)
+            .inspect_err(|err| log::warn!("Failed to parse replay {}: {}", path.display(), err))
+            .ok()?;
+        if replay.version != SAVE_VERSION {
+            log::warn!(
+                "Replay {} is version {}, expected {}",
+                path.display(),
+                replay.version,
+                SAVE_VERSION
+            );
+            return None;
+        }
+        Some(replay)
+    }
+
+    fn path(&self) -> Option<PathBuf> {
+        data_path(REPLAY_DIR).map(|dir| {
+            dir.join(format!(
+                "{}-{}.json",
+                self.recorded_at.format("%Y%m%d-%H%M%S"),
+                self.run.rng.seed
+            ))
+        })
+    }
+
+    fn save(&self) {
+        let Some(path) = self.path() else {
+            log::warn!("No data directory to write replays into");
+            return;
+        };
+        let written = serde_json::to_string(self)
+            .map_err(std::io::Error::other)
+            .and_then(|json| save::write_atomic(&path, &json));
+        match written {
+            Ok(()) => log::info!("Saved replay to {}", path.display()),
+            Err(err) => log::warn!("Failed to write replay {}: {}", path.display(), err),
+        }
+        if let Some(dir) = path.parent() {
+            prune(dir, MAX_REPLAYS);
+        }
+    }
+}
+
+/// Delete all but the newest `keep` replays, whose names start with the
+/// time they were recorded
+fn prune(dir: &Path, keep: usize) {
+    let mut replays = match fs::read_dir(dir) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .collect::<Vec<_>>(),
+        Err(err) => {
+            log::warn!("Failed to list replays in {}: {}", dir.display(), err);
+            return;
+        }
+    };
+    replays.sort();
+    let excess = replays.len().saturating_sub(keep);
+    for path in &replays[..excess] {
+        match fs::remove_file(path) {
+            Ok(()) => log::info!("Deleted old replay {}", path.display()),
+            Err(err) => log::warn!("Failed to delete replay {}: {}", path.display(), err),
+        }
+    }
+}
+
+/// FNV-1a over the serialized battle, stable across runs and platforms
+pub fn state_hash(battle: &Battle) -> u64 {
+    let bytes = serde_json::to_vec(battle).unwrap_or_default();
+    bytes.iter().fold(0xcbf29ce484222325, |hash, &byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// The battle being recorded
+#[derive(Resource, Clone, Debug)]
+pub struct BattleRecording(Replay);
+
+impl BattleRecording {
+    pub fn push(&mut self, action: &BattleAction, hash: u64) {
+        self.0.steps.push(ReplayStep {
+            action: action.clone(),
+            hash,
+        });
+    }
+}
+
+/// A replay being fed back in place of the player, with `+` and `-` to
+/// change its speed
+#[derive(Resource, Clone, Debug)]
+pub struct ReplayPlayback {
+    replay: Replay,
+    path: PathBuf,
+    started: bool,
+    step: usize,
+    /// Inputs resolved and awaiting their hash check
+    resolved: usize,
+    speed: f32,
+    elapsed: f32,
+}
+
+impl ReplayPlayback {
+    /// Read `--replay <path>` and `--speed <multiplier>` from the command line
+    pub fn from_args() -> Option<Self> {
+        let args = std::env::args().collect::<Vec<_>>();
+        let value = |flag: &str| {
+            args.iter()
+                .position(|arg| arg == flag)
+                .and_then(|i| args.get(i + 1))
+        };
+        let path = PathBuf::from(value("--replay")?);
+        let speed = value("--speed")
+            .and_then(|speed| speed.parse::<f32>().ok())
+            .unwrap_or(1.)
+            .clamp(MIN_SPEED, MAX_SPEED);
+        let Some(replay) = Replay::load(&path) else {
+            panic!("Could not load replay {}", path.display());
+        };
+        Some(Self {
+            replay,
+            path,
+            started: false,
+            step: 0,
+            resolved: 0,
+            speed,
+            elapsed: 0.,
+        })
+    }
+
+    /// Compare the battle after a replayed input against the recording
+    pub fn verify(&mut self, battle: &Battle) {
+        let index = self.resolved;
+        self.resolved += 1;
+        let Some(step) = self.replay.steps.get(index) else {
+            self.diverged(format!("input {} was never recorded", index));
+        };
+        let hash = state_hash(battle);
+        if hash != step.hash {
+            self.diverged(format!(
+                "after {:?} (input {}) the battle hashed to {:016x}, recorded {:016x}",
+                step.action, index, hash, step.hash
+            ));
+        }
+    }
+
+    fn diverged(&self, reason: String) -> ! {
+        log::error!("Replay {} diverged: {}", self.path.display(), reason);
+        panic!("Replay {} diverged: {}", self.path.display(), reason);
+    }
+}
+
+fn load_replay_from_args(mut commands: Commands) {
+    if let Some(playback) = ReplayPlayback::from_args() {
+        log::info!(
+            "Playing back {} inputs from {} at {}x",
+            playback.replay.steps.len(),
+            playback.path.display(),
+            playback.speed
+        );
+        commands.insert_resource(playback);
+    }
+}
+
+/// Enter the recorded battle once the libraries it draws on have loaded
+fn start_playback(
+    mut commands: Commands,
+    mut playback: ResMut<ReplayPlayback>,
+    mut app_state: ResMut<NextState<AppState>>,
+    actors_handle: Res<ActorLibraryHandle>,
+    actors: Res<Assets<ActorLibrary>>,
+    cards_handle: Res<CardLibraryHandle>,
+    cards: Res<Assets<CardLibrary>>,
+    classes_handle: Res<ClassLibraryHandle>,
+    classes: Res<Assets<ClassLibrary>>,
+    artefacts_handle: Res<ArtefactLibraryHandle>,
+    artefacts: Res<Assets<ArtefactLibrary>>,
+) {
+    if playback.started
+        || !actors.contains(actors_handle.0.id())
+        || !cards.contains(cards_handle.0.id())
+        || !classes.contains(classes_handle.0.id())
+        || !artefacts.contains(artefacts_handle.0.id())
+    {
+        return;
+    }
+    playback.started = true;
+    commands.insert_resource(playback.replay.run.clone());
+    app_state.set(AppState::Battle);
+}
+
+fn feed_playback(
+    time: Res<Time>,
+    keys: Res<ButtonInput<KeyCode>>,
+    mut playback: ResMut<ReplayPlayback>,
+    mut battle_actions: EventWriter<BattleAction>,
+) {
+    if keys.any_just_pressed([KeyCode::Equal, KeyCode::NumpadAdd]) {
+        playback.speed = (playback.speed * 2.).min(MAX_SPEED);
+        log::info!("Replay speed {}x", playback.speed);
+    }
+    if keys.any_just_pressed([KeyCode::Minus, KeyCode::NumpadSubtract]) {
+        playback.speed = (playback.speed / 2.).max(MIN_SPEED);
+        log::info!("Replay speed {}x", playback.speed);
+    }
+
+    // Wait for the last input to resolve before sending the next
+    if playback.resolved < playback.step {
+        return;
+    }
+    playback.elapsed += time.delta_secs() * playback.speed;
+    if playback.elapsed < STEP_SECONDS {
+        return;
+    }
+    playback.elapsed = 0.;
+    let Some(step) = playback.replay.steps.get(playback.step).cloned() else {
+        return;
+    };
+    playback.step += 1;
+    battle_actions.write(step.action);
+}
+
+/// A finished playback returns to the menu without touching the run
+fn finish_playback(
+    mut commands: Commands,
+    playback: Res<ReplayPlayback>,
+    mut app_state: ResMut<NextState<AppState>>,
+) {
+    if playback.resolved < playback.replay.steps.len() {
+        playback.diverged(format!(
+            "the battle ended after {} of {} inputs",
+            playback.resolved,
+            playback.replay.steps.len()
+        ));
+    }
+    log::info!("Replay {} finished", playback.path.display());
+    commands.remove_resource::<ReplayPlayback>();
+    commands.remove_resource::<RunState>();
+    app_state.set(AppState::Menu);
+}
+
+/// Snapshot the run before the battle takes its seed from it
+pub fn begin_recording(
+    mut commands: Commands,
+    run: Res<RunState>,
+    playback: Option<Res<ReplayPlayback>>,
+) {
+    if playback.is_some() {
+        return;
+    }
+    commands.insert_resource(BattleRecording(Replay {
+        version: SAVE_VERSION,
+        recorded_at: Utc::now(),
+        run: run.clone(),
+        steps: Vec::new(),
+    }));
+}
+
+fn save_recording(mut commands: Commands, recording: Res<BattleRecording>) {
+    recording.0.save();
+    commands.remove_resource::<BattleRecording>();
+}
+
+fn teardown_recording(mut commands: Commands) {
+    commands.remove_resource::<BattleRecording>();
+}
+
+#[derive(Default)]
+pub struct ReplayPlugin;
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, load_replay_from_args)
+            .add_systems(
+                Update,
+                (
+                    start_playback.run_if(in_state(AppState::Menu)),
+                    feed_playback.run_if(in_state(BattleState::Human)),
+                )
+                    .run_if(resource_exists::<ReplayPlayback>),
+            )
+            .add_systems(
+                OnEnter(BattleState::End),
+                (
+                    save_recording.run_if(resource_exists::<BattleRecording>),
+                    finish_playback.run_if(resource_exists::<ReplayPlayback>),
+                ),
+            )
+            .add_systems(OnExit(AppState::Battle), teardown_recording);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prune_keeps_the_newest_replays() {
+        let dir = std::env::temp_dir().join(format!("pipedream-replays-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let names = [
+            "20250101-090000-7.json",
+            "20250102-090000-3.json",
+            "20250101-120000-5.json",
+            "20250103-090000-1.json",
+        ];
+        for name in names {
+            fs::write(dir.join(name), "{}").unwrap();
+        }
+        fs::write(dir.join("notes.txt"), "").unwrap();
+        prune(&dir, 2);
+        let mut left = fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect::<Vec<_>>();
+        left.sort();
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(
+            left,
+            [
+                "20250102-090000-3.json",
+                "20250103-090000-1.json",
+                "notes.txt"
+            ]
+        );
+    }
+}
@@ -0,0 +1,229 @@
+use bevy::prelude::*;
+
+use crate::asset::artefact::{ArtefactLibrary, ArtefactLibraryHandle};
+use crate::asset::card::{CardLibrary, CardLibraryHandle};
+use crate::asset::class::{ClassLibrary, ClassLibraryHandle};
+use crate::asset::dialogue::{DialogueLibrary, DialogueLibraryHandle};
+use crate::asset::rest::{RestAction, RestId, RestLibrary, RestLibraryHandle};
+use crate::asset::stats;
+use crate::deck::DeckAction;
+use crate::dialogue::ActiveDialogue;
+use crate::menu::resource::Language;
+use crate::menu::{BACKDROP, NORMAL_BUTTON, TEXT};
+use crate::run::RunState;
+use crate::run::telemetry::{self, TelemetryEvent};
+use crate::state::AppState;
+
+#[derive(Component)]
+struct OnRestScreen;
+
+#[derive(Component)]
+struct RestButtonAction(RestId);
+
+/// Options offered to every rest site, then those granted by the class and
+/// carried artefacts, leaving out communing while no Thread Slot is free
+fn rest_options(
+    run: &RunState,
+    rests: &RestLibrary,
+    classes: &ClassLibrary,
+    artefacts: &ArtefactLibrary,
+    cards: &CardLibrary,
+) -> Vec<RestId> {
+    let granted = classes
+        .0
+        .get(&run.class)
+        .map(|class| class.rest.clone())
+        .unwrap_or_default()
+        .into_iter()
+        .chain(
+            run.inventory
+                .iter()
+                .filter_map(|id| artefacts.0.get(id))
+                .flat_map(|artefact| artefact.rest.iter().cloned()),
+        )
+        .collect::<Vec<_>>();
+    let thread_free = run.threads(cards) < run.thread_slots;
+    rests
+        .0
+        .iter()
+        .filter(|(id, option)| option.always || granted.contains(id))
+        .filter(|(_, option)| thread_free || !matches!(option.action, RestAction::Commune(_)))
+        .map(|(id, _)| id.clone())
+        .collect()
+}
+
+fn setup_rest(
+    mut commands: Commands,
+    language: Res<Language>,
+    asset_server: Res<AssetServer>,
+    run: Res<RunState>,
+    rests_handle: Res<RestLibraryHandle>,
+    rests: Res<Assets<RestLibrary>>,
+    classes_handle: Res<ClassLibraryHandle>,
+    classes: Res<Assets<ClassLibrary>>,
+    artefacts_handle: Res<ArtefactLibraryHandle>,
+    artefacts: Res<Assets<ArtefactLibrary>>,
+    cards_handle: Res<CardLibraryHandle>,
+    cards: Res<Assets<CardLibrary>>,
+    mut app_state: ResMut<NextState<AppState>>,
+) {
+    let (Some(rests), Some(classes), Some(artefacts), Some(cards)) = (
+        rests.get(rests_handle.0.id()),
+        classes.get(classes_handle.0.id()),
+        artefacts.get(artefacts_handle.0.id()),
+        cards.get(cards_handle.0.id()),
+    ) else {
+        log::warn!("Missing asset libraries while resting");
+        app_state.set(AppState::Campaign);
+        return;
+    };
+    let title = run
+        .map
+        .selected
+        .and_then(|selected| run.map.node(selected))
+        .map(|node| node.title.clone())
+        .unwrap_or_default();
+
+    let font = asset_server.load(language.font_path());
+    let text_font = TextFont {
+        font_size: 16.0,
+        font: font.clone(),
+        ..default()
+    };
+    let panel = commands
+        .spawn((
+            Node {
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                padding: UiRect::all(Val::Px(12.0)),
+                ..default()
+            },
+            BackgroundColor(BACKDROP),
+            children![(
+                Text::new(title),
+                TextFont {
+                    font_size: 24.0,
+                    font,
+                    ..default()
+                },
+                TextColor(TEXT),
+            )],
+        ))
+        .id();
+    for id in rest_options(&run, rests, classes, artefacts, cards) {
+        let Some(option) = rests.0.get(&id) else {
+            continue;
+        };
+        let button = commands
+            .spawn((
+                Button,
+                Node {
+                    min_height: Val::Px(28.0),
+                    margin: UiRect::top(Val::Px(6.0)),
+                    padding: UiRect::horizontal(Val::Px(8.0)),
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                BackgroundColor(NORMAL_BUTTON),
+                RestButtonAction(id),
+                children![(
+                    Text::new(format!("{} - {}", option.name, option.description)),
+                    text_font.clone(),
+                    TextColor(TEXT),
+                )],
+            ))
+            .id();
+        commands.entity(panel).add_child(button);
+    }
+
+    commands
+        .spawn((
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            OnRestScreen,
+        ))
+        .add_child(panel);
+}
+
+fn handle_rest_buttons(
+    mut commands: Commands,
+    interaction_query: Query<
+        (&Interaction, &RestButtonAction),
+        (Changed<Interaction>, With<Button>),
+    >,
+    mut run: ResMut<RunState>,
+    rests_handle: Res<RestLibraryHandle>,
+    rests: Res<Assets<RestLibrary>>,
+    dialogues_handle: Res<DialogueLibraryHandle>,
+    dialogues: Res<Assets<DialogueLibrary>>,
+    mut app_state: ResMut<NextState<AppState>>,
+) {
+    for (interaction, RestButtonAction(id)) in &interaction_query {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        let Some(option) = rests
+            .get(rests_handle.0.id())
+            .and_then(|rests| rests.0.get(id))
+        else {
+            log::warn!("Missing rest option {}", id.0);
+            continue;
+        };
+        log::info!("Rested with {}", option.name);
+        telemetry::record(
+            &run,
+            TelemetryEvent::Choice {
+                source: "rest".to_string(),
+                choice: id.0.clone(),
+            },
+        );
+        match &option.action {
+            RestAction::Recover(percent) => {
+                for resource in [stats::Resource::Health, stats::Resource::Favour] {
+                    let max = run.stats.max(resource).unwrap_or(0);
+                    run.stats.modify(resource, max * *percent as i64 / 100);
+                }
+                app_state.set(AppState::Campaign);
+            }
+            RestAction::Upgrade => {
+                commands.insert_resource(DeckAction::Upgrade);
+                app_state.set(AppState::Deck);
+            }
+            RestAction::Commune(dialogue) => {
+                match dialogues
+                    .get(dialogues_handle.0.id())
+                    .and_then(|dialogues| ActiveDialogue::begin(dialogue, dialogues))
+                {
+                    Some(dialogue) => {
+                        commands.insert_resource(dialogue);
+                        app_state.set(AppState::Dialogue);
+                    }
+                    None => app_state.set(AppState::Campaign),
+                }
+            }
+        }
+        break;
+    }
+}
+
+fn teardown_rest(mut commands: Commands, rest_items_query: Query<Entity, With<OnRestScreen>>) {
+    for rest_entity in &rest_items_query {
+        commands.entity(rest_entity).despawn();
+    }
+}
+
+#[derive(Default)]
+pub struct RestPlugin;
+
+impl Plugin for RestPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(AppState::Rest), setup_rest)
+            .add_systems(Update, handle_rest_buttons.run_if(in_state(AppState::Rest)))
+            .add_systems(OnExit(AppState::Rest), teardown_rest);
+    }
+}
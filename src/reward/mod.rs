@@ -0,0 +1,226 @@
+use bevy::prelude::*;
+
+use crate::asset::card::{CardId, CardLibrary, CardLibraryHandle, Rarity};
+use crate::asset::clan::{ClanLibrary, ClanLibraryHandle};
+use crate::asset::class::{ClassLibrary, ClassLibraryHandle};
+use crate::campaign::map::NodeType;
+use crate::menu::resource::Language;
+use crate::menu::{BACKDROP, NORMAL_BUTTON, TEXT};
+use crate::run::RunState;
+use crate::run::telemetry::{self, TelemetryEvent};
+use crate::state::AppState;
+
+const REWARD_CHOICES: usize = 3;
+const CARD_ART_SIZE: f32 = 64.;
+
+#[derive(Component)]
+struct OnRewardScreen;
+
+#[derive(Component)]
+enum RewardButtonAction {
+    Take(CardId),
+    Skip,
+}
+
+/// A Gullveig ambush gives way to the Wellspring it guarded
+fn after_reward(run: &RunState) -> AppState {
+    let node = run.map.selected.and_then(|selected| run.map.node(selected));
+    match node.map(|node| node.node_type) {
+        Some(NodeType::Wellspring) => AppState::Wellspring,
+        _ => AppState::Campaign,
+    }
+}
+
+fn rarity_color(rarity: Rarity) -> Color {
+    match rarity {
+        Rarity::Common => TEXT,
+        Rarity::Uncommon => Color::srgb(0.4, 0.8, 0.4),
+        Rarity::Rare => Color::srgb(0.4, 0.6, 1.0),
+        Rarity::Legendary => Color::srgb(1.0, 0.7, 0.2),
+    }
+}
+
+fn setup_reward(
+    mut commands: Commands,
+    language: Res<Language>,
+    asset_server: Res<AssetServer>,
+    mut run: ResMut<RunState>,
+    cards_handle: Res<CardLibraryHandle>,
+    cards: Res<Assets<CardLibrary>>,
+    classes_handle: Res<ClassLibraryHandle>,
+    classes: Res<Assets<ClassLibrary>>,
+    clans_handle: Res<ClanLibraryHandle>,
+    clans: Res<Assets<ClanLibrary>>,
+    mut app_state: ResMut<NextState<AppState>>,
+) {
+    let (Some(cards), Some(classes), Some(clans)) = (
+        cards.get(cards_handle.0.id()),
+        classes.get(classes_handle.0.id()),
+        clans.get(clans_handle.0.id()),
+    ) else {
+        log::warn!("Missing asset libraries while drafting rewards");
+        app_state.set(after_reward(&run));
+        return;
+    };
+    let Some(class) = classes.0.get(&run.class) else {
+        log::warn!("Missing class {}", run.class.0);
+        app_state.set(after_reward(&run));
+        return;
+    };
+    let pool = run.card_pool(class, clans, cards);
+    let reward = run.draft(&pool, REWARD_CHOICES, cards);
+    log::info!("Offering {:?}", reward);
+    telemetry::record(
+        &run,
+        TelemetryEvent::CardsOffered {
+            cards: reward.clone(),
+        },
+    );
+
+    let font = asset_server.load(language.font_path());
+    let text_font = TextFont {
+        font_size: 16.0,
+        font: font.clone(),
+        ..default()
+    };
+    let card_node = Node {
+        width: Val::Px(120.0),
+        margin: UiRect::all(Val::Px(8.0)),
+        padding: UiRect::all(Val::Px(8.0)),
+        flex_direction: FlexDirection::Column,
+        align_items: AlignItems::Center,
+        ..default()
+    };
+
+    let choices = commands
+        .spawn(Node {
+            justify_content: JustifyContent::Center,
+            ..default()
+        })
+        .id();
+    for id in &reward {
+        let Some(card) = cards.0.get(id) else {
+            continue;
+        };
+        let button = commands
+            .spawn((
+                Button,
+                card_node.clone(),
+                BackgroundColor(NORMAL_BUTTON),
+                RewardButtonAction::Take(id.clone()),
+                children![
+                    (
+                        Node {
+                            width: Val::Px(CARD_ART_SIZE),
+                            height: Val::Px(CARD_ART_SIZE),
+                            ..default()
+                        },
+                        ImageNode::new(asset_server.load(card.image.clone())),
+                    ),
+                    (
+                        Text::new(card.name.clone()),
+                        text_font.clone(),
+                        TextColor(TEXT)
+                    ),
+                    (
+                        Text::new(format!("{:?}", card.rarity)),
+                        text_font.clone(),
+                        TextColor(rarity_color(card.rarity)),
+                    ),
+                ],
+            ))
+            .id();
+        commands.entity(choices).add_child(button);
+    }
+
+    commands
+        .spawn((
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            BackgroundColor(BACKDROP),
+            OnRewardScreen,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new("choose a card"),
+                TextFont {
+                    font_size: 24.0,
+                    font,
+                    ..default()
+                },
+                TextColor(TEXT),
+            ));
+        })
+        .add_child(choices)
+        .with_children(|parent| {
+            parent.spawn((
+                Button,
+                Node {
+                    margin: UiRect::all(Val::Px(8.0)),
+                    padding: UiRect::horizontal(Val::Px(8.0)),
+                    ..default()
+                },
+                BackgroundColor(NORMAL_BUTTON),
+                RewardButtonAction::Skip,
+                children![(Text::new("skip"), text_font, TextColor(TEXT))],
+            ));
+        });
+}
+
+fn handle_reward_buttons(
+    interaction_query: Query<
+        (&Interaction, &RewardButtonAction),
+        (Changed<Interaction>, With<Button>),
+    >,
+    mut run: ResMut<RunState>,
+    mut app_state: ResMut<NextState<AppState>>,
+) {
+    for (interaction, reward_button_action) in &interaction_query {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        let card = match reward_button_action {
+            RewardButtonAction::Take(card) => {
+                log::info!("Took {}", card.0);
+                run.deck.push(card.clone().into());
+                Some(card.clone())
+            }
+            RewardButtonAction::Skip => {
+                log::info!("Skipped card reward");
+                None
+            }
+        };
+        telemetry::record(&run, TelemetryEvent::CardPicked { card });
+        app_state.set(after_reward(&run));
+        break;
+    }
+}
+
+fn teardown_reward(
+    mut commands: Commands,
+    reward_items_query: Query<Entity, With<OnRewardScreen>>,
+) {
+    for reward_entity in &reward_items_query {
+        commands.entity(reward_entity).despawn();
+    }
+}
+
+#[derive(Default)]
+pub struct RewardPlugin;
+
+impl Plugin for RewardPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(AppState::Reward), setup_reward)
+            .add_systems(
+                Update,
+                handle_reward_buttons.run_if(in_state(AppState::Reward)),
+            )
+            .add_systems(OnExit(AppState::Reward), teardown_reward);
+    }
+}
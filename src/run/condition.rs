@@ -0,0 +1,46 @@
+use serde::{Deserialize, Serialize};
+
+use crate::asset::clan::Clan;
+use crate::asset::class::ClassId;
+use crate::asset::stats::{self, Influence};
+use crate::run::RunState;
+
+/// A requirement on the current run, for gating dialogue and events
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum Condition {
+    Flag(String),
+    NotFlag(String),
+    Influence(Influence, i64),
+    Stat(stats::Resource, i64),
+    /// Influence summed across every type
+    TotalInfluence(i64),
+    Class(ClassId),
+    /// Sworn to the clan and the oath still holds
+    Oath(Clan),
+    OathBroken(Clan),
+    NoOath,
+    All(Vec<Condition>),
+    Any(Vec<Condition>),
+}
+
+impl Condition {
+    pub fn met(&self, run: &RunState) -> bool {
+        match self {
+            Self::Flag(flag) => run.flags.contains(flag),
+            Self::NotFlag(flag) => !run.flags.contains(flag),
+            Self::Influence(influence, at_least) => run.influence(*influence) >= *at_least,
+            Self::Stat(resource, at_least) => run.stats.get(*resource) >= *at_least,
+            Self::TotalInfluence(at_least) => run.influence.values().sum::<i64>() >= *at_least,
+            Self::Class(class) => run.class == *class,
+            Self::Oath(clan) => run
+                .oath
+                .is_some_and(|oath| oath.clan == *clan && !oath.broken),
+            Self::OathBroken(clan) => run
+                .oath
+                .is_some_and(|oath| oath.clan == *clan && oath.broken),
+            Self::NoOath => run.oath.is_none(),
+            Self::All(conditions) => conditions.iter().all(|condition| condition.met(run)),
+            Self::Any(conditions) => conditions.iter().any(|condition| condition.met(run)),
+        }
+    }
+}
@@ -0,0 +1,446 @@
+pub mod condition;
+pub mod record;
+pub mod rng;
+pub mod save;
+pub mod telemetry;
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use bevy::prelude::*;
+use rand::seq::IndexedRandom;
+use serde::{Deserialize, Serialize};
+
+use crate::asset::actor::ActorId;
+use crate::asset::artefact::{ArtefactId, ArtefactLibrary};
+use crate::asset::card::{CardId, CardLibrary, OwnedCard};
+use crate::asset::clan::{Clan, ClanLibrary, ClanOath};
+use crate::asset::class::{Class, ClassId};
+use crate::asset::realm::RealmId;
+use crate::asset::stats::{Influence, Stats};
+use crate::battle::combat::BattleKind;
+use crate::campaign::map::CampaignMap;
+use crate::run::record::RunRecord;
+use crate::run::rng::{RngStream, RunRng};
+
+pub const FIRST_REALM: &str = "niflheim";
+const BASE_THREAD_SLOTS: usize = 1;
+const STARTING_SILVER: u32 = 50;
+/// Fewest cards a deck can be trimmed down to
+const MIN_DECK_SIZE: usize = 5;
+/// Tag for cards only ever granted by swearing an oath
+const OATH_TAG: &str = "oath";
+
+/// A battle started from outside the map, such as by dialogue
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Encounter {
+    pub actor: ActorId,
+    pub kind: BattleKind,
+}
+
+/// The clan the player has sworn to this run
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Oath {
+    pub clan: Clan,
+    pub broken: bool,
+}
+
+/// Everything about the current run that survives between encounters
+#[derive(Resource, Serialize, Deserialize, Clone, Debug)]
+pub struct RunState {
+    pub class: ClassId,
+    pub stats: Stats,
+    pub deck: Vec<OwnedCard>,
+    pub inventory: Vec<ArtefactId>,
+    pub map: CampaignMap,
+    pub flags: BTreeSet<String>,
+    pub rng: RunRng,
+    /// Hacksilver to spend at shops
+    #[serde(default)]
+    pub silver: u32,
+    /// Cards removed at shops, each making the next removal dearer
+    #[serde(default)]
+    pub removals: u32,
+    /// Percentage added to the weight of rare card rewards
+    #[serde(default)]
+    pub rare_weight: u32,
+    /// How many Fate Threads the deck may hold at once
+    #[serde(default)]
+    pub thread_slots: usize,
+    #[serde(default)]
+    pub influence: BTreeMap<Influence, i64>,
+    #[serde(default)]
+    pub encounter: Option<Encounter>,
+    #[serde(default)]
+    pub oath: Option<Oath>,
+    #[serde(default)]
+    pub record: RunRecord,
+}
+
+impl RunState {
+    pub fn new(seed: u64, id: &ClassId, class: &Class) -> Self {
+        let realm = RealmId(FIRST_REALM.to_string());
+        Self {
+            class: id.clone(),
+            stats: class.stats.at_full(),
+            deck: class.deck.iter().cloned().map(OwnedCard::from).collect(),
+            inventory: class.artefacts.clone(),
+            map: CampaignMap {
+                realm: realm.clone(),
+                ..default()
+            },
+            flags: BTreeSet::new(),
+            rng: RunRng::new(seed),
+            silver: STARTING_SILVER,
+            removals: 0,
+            rare_weight: 0,
+            thread_slots: BASE_THREAD_SLOTS,
+            influence: BTreeMap::new(),
+            encounter: None,
+            oath: None,
+            record: RunRecord::new(id, &realm),
+        }
+    }
+
+    /// Become the prestige form of the current class, keeping current
+    /// resources but taking on the new class's limits
+    pub fn prestige(
+        &mut self,
+        id: &ClassId,
+        class: &Class,
+        upgrades: Option<&BTreeMap<CardId, CardId>>,
+    ) {
+        log::info!("{} became {}", self.class.0, id.0);
+        self.class = id.clone();
+        self.stats.max_resources = class.stats.max_resources.clone();
+        self.stats.sleight_of_hand = class.stats.sleight_of_hand.clone();
+        for (&resource, &max) in &class.stats.max_resources {
+            let value = self.stats.get(resource).min(max as i64);
+            self.stats.resources.insert(resource, value);
+        }
+        for (from, to) in upgrades.into_iter().flatten() {
+            for owned in self.deck.iter_mut().filter(|owned| owned.id == *from) {
+                owned.id = to.clone();
+            }
+        }
+    }
+
+    pub fn influence(&self, influence: Influence) -> i64 {
+        self.influence.get(&influence).copied().unwrap_or(0)
+    }
+
+    pub fn gain_influence(&mut self, influence: Influence, amount: i64) {
+        *self.influence.entry(influence).or_default() += amount;
+    }
+
+    pub fn threads(&self, cards: &CardLibrary) -> usize {
+        self.deck
+            .iter()
+            .filter(|owned| {
+                cards
+                    .0
+                    .get(&owned.id)
+                    .is_some_and(|card| card.thread.is_some())
+            })
+            .count()
+    }
+
+    /// Swear the run's one oath, taking the clan's cards, influence and quests
+    pub fn swear_oath(&mut self, clan: Clan, oath: &ClanOath) -> bool {
+        if let Some(sworn) = self.oath {
+            log::info!("Already sworn to {:?}", sworn.clan);
+            return false;
+        }
+        log::info!("Swore an oath to {}", oath.name);
+        self.oath = Some(Oath {
+            clan,
+            broken: false,
+        });
+        self.deck
+            .extend(oath.cards.iter().cloned().map(OwnedCard::from));
+        self.gain_influence(oath.influence, 1);
+        self.flags.extend(oath.unlocks.iter().cloned());
+        true
+    }
+
+    /// Break the sworn oath, closing the clan's quests for the rest of the run
+    pub fn break_oath(&mut self, oath: &ClanOath) -> bool {
+        let Some(sworn) = self.oath.as_mut().filter(|sworn| !sworn.broken) else {
+            return false;
+        };
+        log::info!("Broke the oath to {}", oath.name);
+        sworn.broken = true;
+        for flag in &oath.unlocks {
+            self.flags.remove(flag);
+        }
+        true
+    }
+
+    /// Cards the run may be offered: those sharing a tag with the class or an
+    /// unbroken oath, never those of a clan whose oath was broken, and never
+    /// Fate Threads or cards only granted by oaths
+    pub fn card_pool(
+        &self,
+        class: &Class,
+        clans: &ClanLibrary,
+        cards: &CardLibrary,
+    ) -> Vec<CardId> {
+        let oath_tag = self
+            .oath
+            .and_then(|oath| Some((clans.0.get(&oath.clan)?.tag.as_str(), oath.broken)));
+        let (sworn, forsworn) = match oath_tag {
+            Some((tag, false)) => (Some(tag), None),
+            Some((tag, true)) => (None, Some(tag)),
+            None => (None, None),
+        };
+        cards
+            .0
+            .iter()
+            .filter(|(_, card)| card.thread.is_none())
+            .filter(|(_, card)| !card.tags.iter().any(|tag| tag == OATH_TAG))
+            .filter(|(_, card)| {
+                card.tags
+                    .iter()
+                    .any(|tag| class.tags.contains(tag) || Some(tag.as_str()) == sworn)
+            })
+            .filter(|(_, card)| !card.tags.iter().any(|tag| Some(tag.as_str()) == forsworn))
+            .map(|(id, _)| id.clone())
+            .collect()
+    }
+
+    /// Draw distinct cards from the pool on the loot stream, weighted by
+    /// rarity
+    pub fn draft(&mut self, pool: &[CardId], count: usize, cards: &CardLibrary) -> Vec<CardId> {
+        let rare_weight = self.rare_weight;
+        let mut rng = self.rng.stream(RngStream::Loot);
+        pool.choose_multiple_weighted(&mut rng, count, |id| {
+            cards
+                .0
+                .get(id)
+                .map_or(0., |card| card.rarity.weight(rare_weight))
+        })
+        .map(|drafted| drafted.cloned().collect())
+        .unwrap_or_else(|err| {
+            log::warn!("Failed to draft cards: {}", err);
+            Vec::new()
+        })
+    }
+
+    /// Add a Fate Thread to the deck if a Thread Slot is free
+    pub fn grant_thread(&mut self, card: &CardId, cards: &CardLibrary) -> bool {
+        if self.threads(cards) >= self.thread_slots {
+            log::info!("No free Thread Slot for {}", card.0);
+            return false;
+        }
+        log::info!("Granted {}", card.0);
+        self.deck.push(card.clone().into());
+        true
+    }
+
+    pub fn grant_artefact(&mut self, artefact: &ArtefactId) -> bool {
+        if self.inventory.contains(artefact) {
+            log::info!("Already carrying {}", artefact.0);
+            return false;
+        }
+        log::info!("Granted {}", artefact.0);
+        self.inventory.push(artefact.clone());
+        true
+    }
+
+    /// Draw distinct artefacts not already carried on the loot stream,
+    /// weighted by rarity
+    pub fn draft_artefacts(
+        &mut self,
+        count: usize,
+        artefacts: &ArtefactLibrary,
+    ) -> Vec<ArtefactId> {
+        let rare_weight = self.rare_weight;
+        let candidates = artefacts
+            .0
+            .keys()
+            .filter(|id| !self.inventory.contains(id))
+            .cloned()
+            .collect::<Vec<_>>();
+        let mut rng = self.rng.stream(RngStream::Loot);
+        candidates
+            .choose_multiple_weighted(&mut rng, count, |id| {
+                artefacts
+                    .0
+                    .get(id)
+                    .map_or(0., |artefact| artefact.rarity.weight(rare_weight))
+            })
+            .map(|drafted| drafted.cloned().collect())
+            .unwrap_or_else(|err| {
+                log::warn!("Failed to draft artefacts: {}", err);
+                Vec::new()
+            })
+    }
+
+    /// Swap a carried artefact for a random one not carried, of at least the
+    /// same rarity
+    pub fn exchange_artefact(
+        &mut self,
+        artefact: &ArtefactId,
+        artefacts: &ArtefactLibrary,
+    ) -> Option<ArtefactId> {
+        let index = self.inventory.iter().position(|id| id == artefact)?;
+        let rarity = artefacts.0.get(artefact)?.rarity;
+        let candidates = artefacts
+            .0
+            .iter()
+            .filter(|(id, other)| other.rarity >= rarity && !self.inventory.contains(id))
+            .map(|(id, _)| id.clone())
+            .collect::<Vec<_>>();
+        let exchanged = candidates
+            .choose(&mut self.rng.stream(RngStream::Loot))?
+            .clone();
+        log::info!("Exchanged {} for {}", artefact.0, exchanged.0);
+        self.inventory[index] = exchanged.clone();
+        Some(exchanged)
+    }
+
+    pub fn spend_silver(&mut self, price: u32) -> bool {
+        if self.silver < price {
+            log::info!("Cannot afford {} silver with {}", price, self.silver);
+            return false;
+        }
+        self.silver -= price;
+        true
+    }
+
+    /// Whether the deck is large enough to have a card removed
+    pub fn can_remove_card(&self) -> bool {
+        self.deck.len() > MIN_DECK_SIZE
+    }
+
+    /// Remove the owned card at `index` from the deck for good
+    pub fn remove_card(&mut self, index: usize) -> Option<OwnedCard> {
+        if index >= self.deck.len() || !self.can_remove_card() {
+            return None;
+        }
+        let owned = self.deck.remove(index);
+        log::info!("Removed {}", owned.id.0);
+        self.removals += 1;
+        Some(owned)
+    }
+
+    /// Whether the owned card at `index` has another step on its upgrade track
+    pub fn can_upgrade(&self, index: usize, cards: &CardLibrary) -> bool {
+        self.deck.get(index).is_some_and(|owned| {
+            cards
+                .0
+                .get(&owned.id)
+                .is_some_and(|card| owned.level < card.max_level())
+        })
+    }
+
+    /// Move the owned card at `index` one step along its upgrade track
+    pub fn upgrade(&mut self, index: usize, cards: &CardLibrary) -> bool {
+        if !self.can_upgrade(index, cards) {
+            return false;
+        }
+        let owned = &mut self.deck[index];
+        owned.level += 1;
+        log::info!("Upgraded {} to level {}", owned.id.0, owned.level);
+        true
+    }
+
+    /// Upgrade the least upgraded copy of a card that can still improve
+    pub fn upgrade_card(&mut self, card: &CardId, cards: &CardLibrary) -> bool {
+        let index = (0..self.deck.len())
+            .filter(|&index| self.deck[index].id == *card && self.can_upgrade(index, cards))
+            .min_by_key(|&index| self.deck[index].level);
+        index.is_some_and(|index| self.upgrade(index, cards))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::asset::card::Rarity;
+    use crate::asset::class::ClassLibrary;
+
+    fn cards() -> CardLibrary {
+        serde_json::from_str(include_str!("../../assets/data/cards.json")).unwrap()
+    }
+
+    fn classes() -> ClassLibrary {
+        serde_json::from_str(include_str!("../../assets/data/classes.json")).unwrap()
+    }
+
+    fn new_run(seed: u64, classes: &ClassLibrary) -> RunState {
+        let id = ClassId("berserker".to_string());
+        RunState::new(seed, &id, &classes.0[&id])
+    }
+
+    fn ids(ids: &[&str]) -> Vec<CardId> {
+        ids.iter().map(|id| CardId(id.to_string())).collect()
+    }
+
+    #[test]
+    fn card_pool_keeps_to_class_tags_without_threads_or_oath_cards() {
+        let (cards, classes) = (cards(), classes());
+        let clans: ClanLibrary =
+            serde_json::from_str(include_str!("../../assets/data/clans.json")).unwrap();
+        let run = new_run(0, &classes);
+        let class = &classes.0[&run.class];
+        let pool = run.card_pool(class, &clans, &cards);
+        assert!(!pool.is_empty());
+        for id in &pool {
+            let card = &cards.0[id];
+            assert!(card.thread.is_none(), "{} is a thread", id.0);
+            assert!(!card.tags.iter().any(|tag| tag == OATH_TAG), "{}", id.0);
+            assert!(
+                card.tags.iter().any(|tag| class.tags.contains(tag)),
+                "{}",
+                id.0
+            );
+        }
+    }
+
+    #[test]
+    fn draft_returns_distinct_cards_from_the_pool() {
+        let (cards, classes) = (cards(), classes());
+        let pool = ids(&["punch", "kick", "wild_swing", "taunt", "savage_claw"]);
+        for seed in 0..200 {
+            let mut run = new_run(seed, &classes);
+            let drafted = run.draft(&pool, 3, &cards);
+            assert_eq!(drafted.len(), 3, "seed {}", seed);
+            for (i, id) in drafted.iter().enumerate() {
+                assert!(pool.contains(id), "seed {} drafted {}", seed, id.0);
+                assert!(
+                    !drafted[..i].contains(id),
+                    "seed {} repeated {}",
+                    seed,
+                    id.0
+                );
+            }
+            let all = run.draft(&pool, pool.len() + 2, &cards);
+            assert_eq!(all.iter().collect::<BTreeSet<_>>().len(), pool.len());
+        }
+    }
+
+    #[test]
+    fn draft_follows_rarity_weights() {
+        let (cards, classes) = (cards(), classes());
+        let pool = ids(&["punch", "savage_claw"]);
+        assert_eq!(cards.0[&pool[0]].rarity, Rarity::Common);
+        assert_eq!(cards.0[&pool[1]].rarity, Rarity::Rare);
+        let rare_share = |rare_weight: u32| {
+            let mut run = new_run(5, &classes);
+            run.rare_weight = rare_weight;
+            let rares = (0..4000)
+                .filter(|_| run.draft(&pool, 1, &cards) == pool[1..])
+                .count();
+            rares as f64 / 4000.
+        };
+        // Common cards weigh 60 against a rare's 9, boosted by rare_weight
+        let base = rare_share(0);
+        assert!((0.10..0.16).contains(&base), "rare share {}", base);
+        let boosted = rare_share(100);
+        assert!(
+            (0.19..0.27).contains(&boosted),
+            "boosted rare share {}",
+            boosted
+        );
+    }
+}
@@ -0,0 +1,48 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::asset::card::CardId;
+use crate::asset::class::ClassId;
+use crate::asset::realm::RealmId;
+use crate::profile::Progress;
+
+/// What happened over a run, summarised once it ends
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct RunRecord {
+    pub started: DateTime<Utc>,
+    /// The class the run began as, before any prestige
+    pub class: Option<ClassId>,
+    pub realms: Vec<RealmId>,
+    pub encounters_won: u32,
+    pub cards_played: BTreeMap<CardId, u32>,
+    pub damage_dealt: i64,
+    pub defeated_by: Option<String>,
+    pub victory: bool,
+    /// Meta progress held when the run began
+    pub progress: Progress,
+}
+
+impl RunRecord {
+    pub fn new(class: &ClassId, realm: &RealmId) -> Self {
+        Self {
+            started: Utc::now(),
+            class: Some(class.clone()),
+            realms: vec![realm.clone()],
+            ..Default::default()
+        }
+    }
+
+    /// The most played cards, most first
+    pub fn most_played(&self, count: usize) -> Vec<(&CardId, u32)> {
+        let mut played = self
+            .cards_played
+            .iter()
+            .map(|(id, &times)| (id, times))
+            .collect::<Vec<_>>();
+        played.sort_by_key(|&(_, times)| std::cmp::Reverse(times));
+        played.truncate(count);
+        played
+    }
+}
@@ -0,0 +1,140 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use rand::{Rng, SeedableRng, rngs::StdRng};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug)]
+pub enum RngStream {
+    Map,
+    Battle,
+    Loot,
+    Dialogue,
+    Event,
+}
+
+/// Seeded random streams, so that rolling for loot never perturbs the map or
+/// the next battle and a run can be replayed from its seed and draw counts
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct RunRng {
+    pub seed: u64,
+    #[serde(default)]
+    draws: BTreeMap<RngStream, u64>,
+    #[serde(default)]
+    forced: BTreeSet<RngStream>,
+}
+
+impl RunRng {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            draws: BTreeMap::new(),
+            forced: BTreeSet::new(),
+        }
+    }
+
+    pub fn next_seed(&mut self, stream: RngStream) -> u64 {
+        let draws = self.draws.entry(stream).or_default();
+        *draws += 1;
+        splitmix64(self.seed ^ splitmix64(((stream as u64) << 32) | *draws))
+    }
+
+    pub fn stream(&mut self, stream: RngStream) -> StdRng {
+        StdRng::seed_from_u64(self.next_seed(stream))
+    }
+
+    /// Lock the next roll on a stream to succeed
+    pub fn force(&mut self, stream: RngStream) {
+        self.forced.insert(stream);
+    }
+
+    pub fn roll(&mut self, stream: RngStream, chance: f64) -> bool {
+        if self.forced.remove(&stream) {
+            return true;
+        }
+        self.stream(stream).random_bool(chance.clamp(0., 1.))
+    }
+}
+
+fn splitmix64(value: u64) -> u64 {
+    let mut z = value.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STREAMS: [RngStream; 5] = [
+        RngStream::Map,
+        RngStream::Battle,
+        RngStream::Loot,
+        RngStream::Dialogue,
+        RngStream::Event,
+    ];
+
+    fn seeds(rng: &mut RunRng, stream: RngStream) -> Vec<u64> {
+        (0..8).map(|_| rng.next_seed(stream)).collect()
+    }
+
+    #[test]
+    fn same_seed_gives_same_streams() {
+        let (mut a, mut b) = (RunRng::new(42), RunRng::new(42));
+        for stream in STREAMS {
+            assert_eq!(seeds(&mut a, stream), seeds(&mut b, stream));
+        }
+        let rolls = |rng: &mut RunRng| {
+            (0..32)
+                .map(|_| rng.roll(RngStream::Loot, 0.5))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(rolls(&mut a), rolls(&mut b));
+    }
+
+    #[test]
+    fn different_seeds_give_different_streams() {
+        let (mut a, mut b) = (RunRng::new(1), RunRng::new(2));
+        assert_ne!(
+            seeds(&mut a, RngStream::Battle),
+            seeds(&mut b, RngStream::Battle)
+        );
+    }
+
+    #[test]
+    fn streams_are_independent() {
+        let mut untouched = RunRng::new(7);
+        let mut busy = RunRng::new(7);
+        for _ in 0..5 {
+            busy.next_seed(RngStream::Loot);
+            busy.roll(RngStream::Dialogue, 0.5);
+        }
+        assert_eq!(
+            seeds(&mut busy, RngStream::Battle),
+            seeds(&mut untouched, RngStream::Battle)
+        );
+        let mut rng = RunRng::new(7);
+        let all = STREAMS.map(|stream| rng.next_seed(stream));
+        for (i, seed) in all.iter().enumerate() {
+            assert!(!all[..i].contains(seed));
+        }
+    }
+
+    #[test]
+    fn force_is_consumed_by_one_roll() {
+        let mut rng = RunRng::new(3);
+        rng.force(RngStream::Battle);
+        assert!(!rng.roll(RngStream::Dialogue, 0.));
+        assert!(rng.roll(RngStream::Battle, 0.));
+        assert!(!rng.roll(RngStream::Battle, 0.));
+    }
+
+    #[test]
+    fn force_is_kept_by_stream_draws() {
+        let mut rng = RunRng::new(3);
+        rng.force(RngStream::Battle);
+        rng.stream(RngStream::Battle);
+        rng.next_seed(RngStream::Battle);
+        assert!(rng.roll(RngStream::Battle, 0.));
+    }
+}
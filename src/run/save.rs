@@ -0,0 +1,328 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::run::RunState;
+
+/// Bump whenever the serialized RunState changes shape, and add a migration
+pub const SAVE_VERSION: u64 = 2;
+
+const SAVE_DIR: &str = "pipedream";
+const SAVE_FILE: &str = "save.json";
+const CHECKPOINT_FILE: &str = "checkpoint.json";
+
+/// Upgrades a save from version `i + 1` to version `i + 2`
+const MIGRATIONS: &[fn(&mut Value)] = &[owned_cards];
+
+#[derive(Serialize, Deserialize)]
+struct SaveFile {
+    version: u64,
+    saved_at: DateTime<Utc>,
+    run: RunState,
+}
+
+/// A file in the platform data directory
+pub fn data_path(file: &str) -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join(SAVE_DIR).join(file))
+}
+
+pub fn save_path() -> Option<PathBuf> {
+    data_path(SAVE_FILE)
+}
+
+/// Write beside the old file and swap, so a crash never leaves half a file
+pub fn write_atomic(path: &Path, contents: &str) -> std::io::Result<()> {
+    let temp_path = path.with_extension("tmp");
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(&temp_path, contents)?;
+    fs::rename(&temp_path, path)
+}
+
+pub fn has_save() -> bool {
+    save_path().is_some_and(|path| path.exists())
+}
+
+pub fn save(run: &RunState) {
+    match save_path() {
+        Some(path) => write_run(&path, run),
+        None => log::warn!("No data directory to save into"),
+    }
+}
+
+pub fn load() -> Option<RunState> {
+    read_run(&save_path()?)
+}
+
+pub fn delete() {
+    if let Some(path) = save_path() {
+        remove(&path);
+    }
+}
+
+pub fn has_checkpoint() -> bool {
+    data_path(CHECKPOINT_FILE).is_some_and(|path| path.exists())
+}
+
+/// Keep the run as it leaves a Wellspring, surviving the run save being
+/// deleted on defeat
+pub fn checkpoint(run: &RunState) {
+    match data_path(CHECKPOINT_FILE) {
+        Some(path) => write_run(&path, run),
+        None => log::warn!("No data directory to save a checkpoint into"),
+    }
+}
+
+/// Take the run back from the last Wellspring reached, which only allows
+/// one retry per checkpoint
+pub fn resume_checkpoint() -> Option<RunState> {
+    let path = data_path(CHECKPOINT_FILE)?;
+    let run = read_run(&path)?;
+    remove(&path);
+    Some(run)
+}
+
+pub fn delete_checkpoint() {
+    if let Some(path) = data_path(CHECKPOINT_FILE) {
+        remove(&path);
+    }
+}
+
+fn write_run(path: &Path, run: &RunState) {
+    let save = SaveFile {
+        version: SAVE_VERSION,
+        saved_at: Utc::now(),
+        run: run.clone(),
+    };
+    let json = match serde_json::to_string_pretty(&save) {
+        Ok(json) => json,
+        Err(err) => {
+            log::warn!("Failed to serialize save: {}", err);
+            return;
+        }
+    };
+    match write_atomic(path, &json) {
+        Ok(()) => log::info!("Saved run to {}", path.display()),
+        Err(err) => log::warn!("Failed to write save {}: {}", path.display(), err),
+    }
+}
+
+fn read_run(path: &Path) -> Option<RunState> {
+    let json = fs::read_to_string(path)
+        .inspect_err(|err| log::warn!("Failed to read save {}: {}", path.display(), err))
+        .ok()?;
+    let mut value = serde_json::from_str::<Value>(&json)
+        .inspect_err(|err| log::warn!("Failed to parse save {}: {}", path.display(), err))
+        .ok()?;
+    migrate(&mut value)?;
+    let save = serde_json::from_value::<SaveFile>(value)
+        .inspect_err(|err| log::warn!("Failed to load save {}: {}", path.display(), err))
+        .ok()?;
+    log::info!("Loaded run saved at {}", save.saved_at);
+    Some(save.run)
+}
+
+fn remove(path: &Path) {
+    if !path.exists() {
+        return;
+    }
+    if let Err(err) = fs::remove_file(path) {
+        log::warn!("Failed to delete save {}: {}", path.display(), err);
+    }
+}
+
+/// Version 2 tracks an upgrade level for every card in the run deck
+fn owned_cards(value: &mut Value) {
+    let deck = value
+        .get_mut("run")
+        .and_then(|run| run.get_mut("deck"))
+        .and_then(Value::as_array_mut);
+    if let Some(deck) = deck {
+        for card in deck {
+            *card = serde_json::json!({ "id": card.take(), "level": 0 });
+        }
+    }
+}
+
+/// Walk a save forward one version at a time until it matches SAVE_VERSION
+fn migrate(value: &mut Value) -> Option<()> {
+    loop {
+        let Some(version) = value.get("version").and_then(Value::as_u64) else {
+            log::warn!("Save has no version");
+            return None;
+        };
+        if version == SAVE_VERSION {
+            return Some(());
+        }
+        let Some(migration) = version
+            .checked_sub(1)
+            .and_then(|i| MIGRATIONS.get(i as usize))
+        else {
+            log::warn!(
+                "Cannot migrate save from version {} to {}",
+                version,
+                SAVE_VERSION
+            );
+            return None;
+        };
+        log::info!("Migrating save from version {}", version);
+        migration(value);
+        value["version"] = Value::from(version + 1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A version 1 save, from before cards in the deck carried a level
+    const SAVE_V1: &str = r#"{
+        "version": 1,
+        "saved_at": "2025-01-01T00:00:00Z",
+        "run": {
+            "class": "berserker",
+            "stats": {
+                "resources": { "Health": 21, "Stamina": 4 },
+                "max_resources": { "Health": 30, "Stamina": 4 }
+            },
+            "deck": ["punch", "punch", "kick", "wild_swing"],
+            "inventory": [],
+            "map": {
+                "realm": "niflheim",
+                "background": null,
+                "nodes": [
+                    {
+                        "node_type": "Combat",
+                        "title": "Frozen Pass",
+                        "layer": 0,
+                        "lane": 0,
+                        "edges": [1],
+                        "encounter": "frost_draugr",
+                        "image": null,
+                        "requires": []
+                    },
+                    {
+                        "node_type": "Wellspring",
+                        "title": "Hvergelmir",
+                        "layer": 1,
+                        "lane": 0,
+                        "edges": [],
+                        "encounter": null,
+                        "image": null,
+                        "requires": []
+                    }
+                ],
+                "current": 0,
+                "selected": null
+            },
+            "flags": ["met_urdr"],
+            "rng": { "seed": 7, "draws": { "Map": 1, "Battle": 3 } }
+        }
+    }"#;
+
+    /// A version 2 save, with upgraded copies in the deck
+    const SAVE_V2: &str = r#"{
+        "version": 2,
+        "saved_at": "2025-01-02T00:00:00Z",
+        "run": {
+            "class": "berserker",
+            "stats": { "resources": { "Health": 12 }, "max_resources": { "Health": 30 } },
+            "deck": [
+                { "id": "punch", "level": 2 },
+                { "id": "kick", "level": 0 },
+                { "id": "wild_swing", "level": 1 }
+            ],
+            "inventory": ["wolf_pelt"],
+            "map": {
+                "realm": "niflheim",
+                "background": null,
+                "nodes": [],
+                "current": null,
+                "selected": null
+            },
+            "flags": [],
+            "rng": { "seed": 7, "draws": {}, "forced": [] },
+            "silver": 35,
+            "removals": 1
+        }
+    }"#;
+
+    fn read_fixture(name: &str, json: &str) -> Option<RunState> {
+        let path = std::env::temp_dir().join(format!("pipedream-{}-{}", std::process::id(), name));
+        fs::write(&path, json).unwrap();
+        let run = read_run(&path);
+        fs::remove_file(&path).unwrap();
+        run
+    }
+
+    fn deck(run: &RunState) -> Vec<(&str, usize)> {
+        run.deck
+            .iter()
+            .map(|owned| (owned.id.0.as_str(), owned.level))
+            .collect()
+    }
+
+    #[test]
+    fn every_older_version_has_a_migration() {
+        assert_eq!(MIGRATIONS.len() as u64, SAVE_VERSION - 1);
+    }
+
+    #[test]
+    fn migrates_v1_deck_to_unupgraded_cards() {
+        let run = read_fixture("v1.json", SAVE_V1).unwrap();
+        assert_eq!(
+            deck(&run),
+            [("punch", 0), ("punch", 0), ("kick", 0), ("wild_swing", 0)]
+        );
+        assert_eq!(run.class.0, "berserker");
+        assert_eq!(run.stats.get(crate::asset::stats::Resource::Health), 21);
+        assert_eq!(run.map.nodes.len(), 2);
+        assert!(run.flags.contains("met_urdr"));
+        assert_eq!(run.rng.seed, 7);
+    }
+
+    #[test]
+    fn loads_v2_keeping_card_levels() {
+        let run = read_fixture("v2.json", SAVE_V2).unwrap();
+        assert_eq!(deck(&run), [("punch", 2), ("kick", 0), ("wild_swing", 1)]);
+        assert_eq!(run.silver, 35);
+        assert_eq!(run.removals, 1);
+    }
+
+    #[test]
+    fn migrating_twice_leaves_a_save_unchanged() {
+        let mut value = serde_json::from_str::<Value>(SAVE_V1).unwrap();
+        migrate(&mut value).unwrap();
+        let migrated = value.clone();
+        migrate(&mut value).unwrap();
+        assert_eq!(value, migrated);
+        assert_eq!(value["version"], SAVE_VERSION);
+    }
+
+    #[test]
+    fn rejects_unknown_versions() {
+        for version in [0, SAVE_VERSION + 1] {
+            let mut value = serde_json::json!({ "version": version });
+            assert!(migrate(&mut value).is_none());
+        }
+        assert!(migrate(&mut serde_json::json!({})).is_none());
+    }
+
+    #[test]
+    fn written_runs_read_back_unchanged() {
+        let run = read_fixture("v2-round-trip.json", SAVE_V2).unwrap();
+        let path =
+            std::env::temp_dir().join(format!("pipedream-{}-written.json", std::process::id()));
+        write_run(&path, &run);
+        let read = read_run(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(
+            serde_json::to_value(&read).unwrap(),
+            serde_json::to_value(&run).unwrap()
+        );
+    }
+}
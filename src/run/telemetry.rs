@@ -0,0 +1,107 @@
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::asset::actor::ActorId;
+use crate::asset::card::CardId;
+use crate::asset::class::ClassId;
+use crate::battle::combat::BattleKind;
+use crate::run::RunState;
+use crate::run::save::data_path;
+
+pub const TELEMETRY_DIR: &str = "telemetry";
+
+/// Something worth balancing around, written as one JSON line of the run's
+/// telemetry file
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(tag = "event")]
+pub enum TelemetryEvent {
+    RunStarted {
+        class: ClassId,
+        seed: u64,
+    },
+    EncounterStarted {
+        enemies: Vec<ActorId>,
+        kind: BattleKind,
+    },
+    EncounterEnded {
+        enemies: Vec<ActorId>,
+        kind: BattleKind,
+        won: bool,
+        turns: u32,
+        damage_taken: i64,
+        damage_dealt: i64,
+    },
+    CardsOffered {
+        cards: Vec<CardId>,
+    },
+    /// A card taken into the deck, or none when the offer was skipped
+    CardPicked {
+        card: Option<CardId>,
+    },
+    CardPlayed {
+        card: CardId,
+    },
+    /// A card play taken back, retracting its `CardPlayed`
+    CardUndone {
+        card: CardId,
+    },
+    /// A choice made in dialogue, an event or at a rest site
+    Choice {
+        source: String,
+        choice: String,
+    },
+    Death {
+        cause: String,
+    },
+    RunEnded {
+        victory: bool,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TelemetryRecord {
+    pub time: DateTime<Utc>,
+    pub realm: String,
+    #[serde(flatten)]
+    pub event: TelemetryEvent,
+}
+
+/// One file per run, named for when it started and its seed
+pub fn telemetry_path(run: &RunState) -> Option<PathBuf> {
+    data_path(TELEMETRY_DIR).map(|dir| {
+        dir.join(format!(
+            "{}-{}.jsonl",
+            run.record.started.format("%Y%m%d-%H%M%S"),
+            run.rng.seed
+        ))
+    })
+}
+
+/// Append an event to the run's telemetry, never failing the game over it
+pub fn record(run: &RunState, event: TelemetryEvent) {
+    let Some(path) = telemetry_path(run) else {
+        log::warn!("No data directory to write telemetry into");
+        return;
+    };
+    let record = TelemetryRecord {
+        time: Utc::now(),
+        realm: run.map.realm.0.clone(),
+        event,
+    };
+    let written = serde_json::to_string(&record)
+        .map_err(std::io::Error::other)
+        .and_then(|line| {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            let mut file = OpenOptions::new().create(true).append(true).open(&path)?;
+            writeln!(file, "{}", line)
+        });
+    if let Err(err) = written {
+        log::warn!("Failed to write telemetry {}: {}", path.display(), err);
+    }
+}
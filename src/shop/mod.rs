@@ -0,0 +1,371 @@
+use bevy::prelude::*;
+
+use crate::asset::artefact::{ArtefactId, ArtefactLibrary, ArtefactLibraryHandle};
+use crate::asset::card::{CardId, CardLibrary, CardLibraryHandle};
+use crate::asset::clan::{ClanLibrary, ClanLibraryHandle};
+use crate::asset::class::{ClassLibrary, ClassLibraryHandle};
+use crate::cards::event::{NodeInteraction, NodeInteractionType};
+use crate::cards::{InteractiveNode, spawn_card_node};
+use crate::deck::DeckAction;
+use crate::input::BackButton;
+use crate::menu::resource::Language;
+use crate::menu::{BACKDROP, NORMAL_BUTTON, TEXT};
+use crate::run::{RunState, save};
+use crate::state::AppState;
+
+const SHOP_CARDS: usize = 5;
+const SHOP_ARTEFACTS: usize = 2;
+const SHOP_CARD_Y: f32 = 64.;
+const SHOP_CARD_SPACING: f32 = 64.;
+const PRICE_OFFSET: Vec3 = vec3(0., -40., 1.);
+const ARTEFACT_PRICE_FACTOR: u32 = 2;
+const REMOVAL_PRICE: u32 = 50;
+const REMOVAL_PRICE_STEP: u32 = 25;
+/// Discount per point of the sworn clan's influence
+const INFLUENCE_DISCOUNT: u32 = 5;
+const MAX_DISCOUNT: u32 = 50;
+const UNAFFORDABLE: Color = Color::srgb(0.6, 0.3, 0.3);
+
+/// The wares of the shop being visited, kept while away removing a card
+#[derive(Resource, Clone, Debug, Default)]
+struct ShopStock {
+    title: String,
+    discount: u32,
+    cards: Vec<(CardId, u32)>,
+    artefacts: Vec<(ArtefactId, u32)>,
+}
+
+impl ShopStock {
+    fn price(&self, base: u32) -> u32 {
+        base * (100 - self.discount) / 100
+    }
+
+    fn removal_price(&self, run: &RunState) -> u32 {
+        self.price(REMOVAL_PRICE + REMOVAL_PRICE_STEP * run.removals)
+    }
+}
+
+#[derive(Component)]
+struct OnShopScreen;
+
+#[derive(Component)]
+struct ShopCard(usize);
+
+#[derive(Component)]
+enum ShopButtonAction {
+    BuyArtefact(usize),
+    RemoveCard,
+    Leave,
+}
+
+/// Percentage off every price, from carried artefacts and the influence of
+/// an unbroken oath's clan
+fn discount(run: &RunState, artefacts: &ArtefactLibrary, clans: &ClanLibrary) -> u32 {
+    let carried = run
+        .inventory
+        .iter()
+        .filter_map(|id| artefacts.0.get(id))
+        .map(|artefact| artefact.discount)
+        .sum::<u32>();
+    let sworn = run
+        .oath
+        .filter(|oath| !oath.broken)
+        .and_then(|oath| clans.0.get(&oath.clan))
+        .map_or(0, |oath| run.influence(oath.influence).max(0) as u32);
+    (carried + sworn * INFLUENCE_DISCOUNT).min(MAX_DISCOUNT)
+}
+
+fn setup_shop(
+    mut commands: Commands,
+    stock: Option<ResMut<ShopStock>>,
+    mut run: ResMut<RunState>,
+    cards_handle: Res<CardLibraryHandle>,
+    cards: Res<Assets<CardLibrary>>,
+    classes_handle: Res<ClassLibraryHandle>,
+    classes: Res<Assets<ClassLibrary>>,
+    clans_handle: Res<ClanLibraryHandle>,
+    clans: Res<Assets<ClanLibrary>>,
+    artefacts_handle: Res<ArtefactLibraryHandle>,
+    artefacts: Res<Assets<ArtefactLibrary>>,
+) {
+    // Returning from removing a card keeps the wares already drawn
+    if let Some(mut stock) = stock {
+        stock.set_changed();
+        return;
+    }
+    let (Some(cards), Some(class), Some(clans), Some(artefacts)) = (
+        cards.get(cards_handle.0.id()),
+        classes
+            .get(classes_handle.0.id())
+            .and_then(|classes| classes.0.get(&run.class)),
+        clans.get(clans_handle.0.id()),
+        artefacts.get(artefacts_handle.0.id()),
+    ) else {
+        log::warn!("Missing asset libraries while stocking shop");
+        commands.insert_resource(ShopStock::default());
+        return;
+    };
+
+    let title = run
+        .map
+        .selected
+        .and_then(|selected| run.map.node(selected))
+        .map(|node| node.title.clone())
+        .unwrap_or_default();
+    let mut stock = ShopStock {
+        title,
+        discount: discount(&run, artefacts, clans),
+        ..default()
+    };
+    let pool = run.card_pool(class, clans, cards);
+    for id in run.draft(&pool, SHOP_CARDS, cards) {
+        if let Some(card) = cards.0.get(&id) {
+            stock.cards.push((id, stock.price(card.rarity.price())));
+        }
+    }
+    for id in run.draft_artefacts(SHOP_ARTEFACTS, artefacts) {
+        if let Some(artefact) = artefacts.0.get(&id) {
+            let price = stock.price(artefact.rarity.price() * ARTEFACT_PRICE_FACTOR);
+            stock.artefacts.push((id, price));
+        }
+    }
+    log::info!("Stocked {:?}", stock);
+    commands.insert_resource(stock);
+}
+
+fn spawn_shop_ui(
+    mut commands: Commands,
+    language: Res<Language>,
+    asset_server: Res<AssetServer>,
+    stock: Res<ShopStock>,
+    run: Res<RunState>,
+    cards_handle: Res<CardLibraryHandle>,
+    cards: Res<Assets<CardLibrary>>,
+    artefacts_handle: Res<ArtefactLibraryHandle>,
+    artefacts: Res<Assets<ArtefactLibrary>>,
+    shop_items_query: Query<Entity, With<OnShopScreen>>,
+) {
+    for shop_entity in &shop_items_query {
+        commands.entity(shop_entity).despawn();
+    }
+
+    let font = asset_server.load(language.font_path());
+    let text_font = TextFont {
+        font_size: 16.0,
+        font: font.clone(),
+        ..default()
+    };
+    let price_color = |price: u32| {
+        if price > run.silver {
+            UNAFFORDABLE
+        } else {
+            TEXT
+        }
+    };
+
+    if let Some(cards) = cards.get(cards_handle.0.id()) {
+        for (i, (id, price)) in stock.cards.iter().enumerate() {
+            let Some(card) = cards.0.get(id) else {
+                continue;
+            };
+            let position = vec2(
+                (i as f32 - (stock.cards.len() as f32 - 1.) / 2.) * SHOP_CARD_SPACING,
+                SHOP_CARD_Y,
+            );
+            spawn_card_node(
+                &mut commands,
+                &asset_server,
+                card,
+                InteractiveNode::fixed(),
+                position,
+            )
+            .insert((OnShopScreen, ShopCard(i)))
+            .with_child((
+                Text2d::new(price.to_string()),
+                text_font.clone(),
+                TextColor(price_color(*price)),
+                Transform::from_translation(PRICE_OFFSET),
+            ));
+        }
+    }
+
+    let mut buttons = Vec::new();
+    if let Some(artefacts) = artefacts.get(artefacts_handle.0.id()) {
+        for (i, (id, price)) in stock.artefacts.iter().enumerate() {
+            if let Some(artefact) = artefacts.0.get(id) {
+                buttons.push((
+                    ShopButtonAction::BuyArtefact(i),
+                    format!("{} ({}) - {}", artefact.name, artefact.description, price),
+                    price_color(*price),
+                ));
+            }
+        }
+    }
+    let removal = stock.removal_price(&run);
+    buttons.push((
+        ShopButtonAction::RemoveCard,
+        format!("remove a card - {}", removal),
+        price_color(removal),
+    ));
+    buttons.push((ShopButtonAction::Leave, "leave".to_string(), TEXT));
+
+    let panel = commands
+        .spawn((
+            Node {
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                padding: UiRect::all(Val::Px(12.0)),
+                ..default()
+            },
+            BackgroundColor(BACKDROP),
+            children![
+                (
+                    Text::new(stock.title.clone()),
+                    TextFont {
+                        font_size: 24.0,
+                        font,
+                        ..default()
+                    },
+                    TextColor(TEXT),
+                ),
+                (
+                    Text::new(format!("{} silver", run.silver)),
+                    text_font.clone(),
+                    TextColor(TEXT),
+                ),
+            ],
+        ))
+        .id();
+    for (action, text, color) in buttons {
+        let leave = matches!(action, ShopButtonAction::Leave);
+        let button = commands
+            .spawn((
+                Button,
+                Node {
+                    min_height: Val::Px(28.0),
+                    margin: UiRect::top(Val::Px(6.0)),
+                    padding: UiRect::horizontal(Val::Px(8.0)),
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                BackgroundColor(NORMAL_BUTTON),
+                action,
+                children![(Text::new(text), text_font.clone(), TextColor(color))],
+            ))
+            .id();
+        if leave {
+            commands.entity(button).insert(BackButton);
+        }
+        commands.entity(panel).add_child(button);
+    }
+
+    commands
+        .spawn((
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                align_items: AlignItems::End,
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            OnShopScreen,
+        ))
+        .add_child(panel);
+}
+
+fn handle_shop_cards(
+    mut node_interaction_events: EventReader<NodeInteraction>,
+    shop_cards_query: Query<&ShopCard>,
+    mut stock: ResMut<ShopStock>,
+    mut run: ResMut<RunState>,
+) {
+    for interaction in node_interaction_events.read() {
+        if interaction.interaction_type != NodeInteractionType::LeftClick {
+            continue;
+        }
+        let Ok(ShopCard(index)) = shop_cards_query.get(interaction.entity) else {
+            continue;
+        };
+        let Some((id, price)) = stock.cards.get(*index).cloned() else {
+            continue;
+        };
+        if run.spend_silver(price) {
+            log::info!("Bought {} for {}", id.0, price);
+            run.deck.push(id.into());
+            stock.cards.remove(*index);
+        }
+        break;
+    }
+}
+
+fn handle_shop_buttons(
+    mut commands: Commands,
+    interaction_query: Query<
+        (&Interaction, &ShopButtonAction),
+        (Changed<Interaction>, With<Button>),
+    >,
+    mut stock: ResMut<ShopStock>,
+    mut run: ResMut<RunState>,
+    mut app_state: ResMut<NextState<AppState>>,
+) {
+    for (interaction, shop_button_action) in &interaction_query {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        match shop_button_action {
+            ShopButtonAction::BuyArtefact(index) => {
+                let Some((id, price)) = stock.artefacts.get(*index).cloned() else {
+                    continue;
+                };
+                if run.spend_silver(price) {
+                    log::info!("Bought {} for {}", id.0, price);
+                    run.grant_artefact(&id);
+                    stock.artefacts.remove(*index);
+                }
+            }
+            ShopButtonAction::RemoveCard => {
+                let price = stock.removal_price(&run);
+                if run.silver >= price && run.can_remove_card() {
+                    commands.insert_resource(DeckAction::Remove { price });
+                    app_state.set(AppState::Deck);
+                }
+            }
+            ShopButtonAction::Leave => {
+                commands.remove_resource::<ShopStock>();
+                run.map.advance();
+                save::save(&run);
+                app_state.set(AppState::Campaign);
+            }
+        }
+        break;
+    }
+}
+
+fn teardown_shop(mut commands: Commands, shop_items_query: Query<Entity, With<OnShopScreen>>) {
+    for shop_entity in &shop_items_query {
+        commands.entity(shop_entity).despawn();
+    }
+}
+
+#[derive(Default)]
+pub struct ShopPlugin;
+
+impl Plugin for ShopPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(AppState::Shop), setup_shop)
+            .add_systems(
+                Update,
+                (
+                    spawn_shop_ui.run_if(
+                        resource_exists_and_changed::<ShopStock>
+                            .or(resource_exists_and_changed::<RunState>),
+                    ),
+                    handle_shop_cards,
+                    handle_shop_buttons,
+                )
+                    .chain()
+                    .run_if(in_state(AppState::Shop).and(resource_exists::<ShopStock>)),
+            )
+            .add_systems(OnExit(AppState::Shop), teardown_shop);
+    }
+}
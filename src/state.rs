@@ -6,6 +6,13 @@ pub enum AppState {
     Menu,
     Campaign,
     Battle,
+    Dialogue,
+    Reward,
+    Deck,
+    Wellspring,
+    Shop,
+    Rest,
+    Event,
     GameOver,
 }
 
@@ -14,10 +21,12 @@ pub enum MenuState {
     None,
     #[default]
     MainMenu,
+    ClassSelect,
     Settings,
 }
 
-#[derive(Default, States, Debug, Hash, PartialEq, Eq, Clone)]
+#[derive(Default, SubStates, Debug, Hash, PartialEq, Eq, Clone)]
+#[source(AppState = AppState::Battle)]
 pub enum BattleState {
     #[default]
     Begin,
@@ -33,6 +42,6 @@ impl Plugin for StatesPlugin {
     fn build(&self, app: &mut App) {
         app.init_state::<AppState>()
             .init_state::<MenuState>()
-            .init_state::<BattleState>();
+            .add_sub_state::<BattleState>();
     }
 }
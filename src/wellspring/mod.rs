@@ -0,0 +1,306 @@
+use std::collections::BTreeSet;
+
+use bevy::prelude::*;
+
+use crate::asset::artefact::{ArtefactId, ArtefactLibrary, ArtefactLibraryHandle};
+use crate::asset::card::{CardLibrary, CardLibraryHandle, Rarity};
+use crate::asset::clan::{ClanLibrary, ClanLibraryHandle};
+use crate::asset::class::{ClassLibrary, ClassLibraryHandle};
+use crate::asset::stats;
+use crate::input::BackButton;
+use crate::menu::resource::Language;
+use crate::menu::{BACKDROP, NORMAL_BUTTON, TEXT};
+use crate::profile::Profile;
+use crate::run::{RunState, save};
+use crate::state::AppState;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum WellspringService {
+    Heal,
+    Exchange,
+    RareCard,
+}
+
+/// The services already taken at this Wellspring, each offered once
+#[derive(Resource, Clone, Debug, Default)]
+struct WellspringVisit {
+    title: String,
+    used: BTreeSet<WellspringService>,
+    messages: Vec<String>,
+}
+
+#[derive(Component)]
+struct OnWellspringScreen;
+
+#[derive(Component)]
+enum WellspringButtonAction {
+    Heal,
+    Exchange(ArtefactId),
+    RareCard,
+    Leave,
+}
+
+/// Reaching a Wellspring, and surviving the Gullveig form guarding it, each
+/// open a Thread Slot the first time
+fn setup_wellspring(
+    mut commands: Commands,
+    mut run: ResMut<RunState>,
+    mut profile: ResMut<Profile>,
+) {
+    let node = run.map.selected.and_then(|selected| run.map.node(selected));
+    let title = node.map(|node| node.title.clone()).unwrap_or_default();
+    let boss = node.and_then(|node| node.encounter.clone());
+    let mut visit = WellspringVisit { title, ..default() };
+
+    let realm = run.map.realm.clone();
+    if profile.wellsprings_reached.insert(realm) {
+        profile.thread_slots += 1;
+        run.thread_slots += 1;
+        visit
+            .messages
+            .push("The Wellspring opens a Thread Slot".to_string());
+    }
+    if let Some(boss) = boss
+        && profile.gullveig_forms_survived.insert(boss.0.clone())
+    {
+        profile.thread_slots += 1;
+        run.thread_slots += 1;
+        visit
+            .messages
+            .push("Surviving Gullveig opens a Thread Slot".to_string());
+    }
+    commands.insert_resource(visit);
+}
+
+fn spawn_wellspring_ui(
+    mut commands: Commands,
+    language: Res<Language>,
+    asset_server: Res<AssetServer>,
+    visit: Res<WellspringVisit>,
+    run: Res<RunState>,
+    artefacts_handle: Res<ArtefactLibraryHandle>,
+    artefacts: Res<Assets<ArtefactLibrary>>,
+    wellspring_items_query: Query<Entity, With<OnWellspringScreen>>,
+) {
+    for wellspring_entity in &wellspring_items_query {
+        commands.entity(wellspring_entity).despawn();
+    }
+
+    let font = asset_server.load(language.font_path());
+    let text_font = TextFont {
+        font_size: 16.0,
+        font: font.clone(),
+        ..default()
+    };
+    let button_node = Node {
+        min_height: Val::Px(28.0),
+        margin: UiRect::top(Val::Px(6.0)),
+        padding: UiRect::horizontal(Val::Px(8.0)),
+        align_items: AlignItems::Center,
+        ..default()
+    };
+
+    let mut buttons = Vec::new();
+    if !visit.used.contains(&WellspringService::Heal) {
+        buttons.push((
+            WellspringButtonAction::Heal,
+            "drink from the well".to_string(),
+        ));
+    }
+    if !visit.used.contains(&WellspringService::Exchange)
+        && let Some(artefacts) = artefacts.get(artefacts_handle.0.id())
+    {
+        for id in &run.inventory {
+            if let Some(artefact) = artefacts.0.get(id) {
+                buttons.push((
+                    WellspringButtonAction::Exchange(id.clone()),
+                    format!("exchange {}", artefact.name),
+                ));
+            }
+        }
+    }
+    if !visit.used.contains(&WellspringService::RareCard) {
+        buttons.push((
+            WellspringButtonAction::RareCard,
+            "take a rare card".to_string(),
+        ));
+    }
+    buttons.push((WellspringButtonAction::Leave, "leave".to_string()));
+
+    let panel = commands
+        .spawn((
+            Node {
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                padding: UiRect::all(Val::Px(12.0)),
+                ..default()
+            },
+            BackgroundColor(BACKDROP),
+            children![(
+                Text::new(visit.title.clone()),
+                TextFont {
+                    font_size: 24.0,
+                    font,
+                    ..default()
+                },
+                TextColor(TEXT),
+            )],
+        ))
+        .id();
+    for message in &visit.messages {
+        let text = commands
+            .spawn((
+                Text::new(message.clone()),
+                text_font.clone(),
+                TextColor(TEXT),
+            ))
+            .id();
+        commands.entity(panel).add_child(text);
+    }
+    for (action, text) in buttons {
+        let leave = matches!(action, WellspringButtonAction::Leave);
+        let button = commands
+            .spawn((
+                Button,
+                button_node.clone(),
+                BackgroundColor(NORMAL_BUTTON),
+                action,
+                children![(Text::new(text), text_font.clone(), TextColor(TEXT))],
+            ))
+            .id();
+        if leave {
+            commands.entity(button).insert(BackButton);
+        }
+        commands.entity(panel).add_child(button);
+    }
+
+    commands
+        .spawn((
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            OnWellspringScreen,
+        ))
+        .add_child(panel);
+}
+
+fn handle_wellspring_buttons(
+    interaction_query: Query<
+        (&Interaction, &WellspringButtonAction),
+        (Changed<Interaction>, With<Button>),
+    >,
+    mut visit: ResMut<WellspringVisit>,
+    mut run: ResMut<RunState>,
+    cards_handle: Res<CardLibraryHandle>,
+    cards: Res<Assets<CardLibrary>>,
+    classes_handle: Res<ClassLibraryHandle>,
+    classes: Res<Assets<ClassLibrary>>,
+    clans_handle: Res<ClanLibraryHandle>,
+    clans: Res<Assets<ClanLibrary>>,
+    artefacts_handle: Res<ArtefactLibraryHandle>,
+    artefacts: Res<Assets<ArtefactLibrary>>,
+    mut app_state: ResMut<NextState<AppState>>,
+) {
+    for (interaction, wellspring_button_action) in &interaction_query {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        match wellspring_button_action {
+            WellspringButtonAction::Heal => {
+                run.stats.refill(stats::Resource::Health);
+                run.stats.refill(stats::Resource::Favour);
+                visit.used.insert(WellspringService::Heal);
+                visit.messages.push("The waters restore you".to_string());
+            }
+            WellspringButtonAction::Exchange(artefact) => {
+                let Some(artefacts) = artefacts.get(artefacts_handle.0.id()) else {
+                    log::warn!("Missing artefact library while exchanging {}", artefact.0);
+                    continue;
+                };
+                visit.used.insert(WellspringService::Exchange);
+                let message = match run.exchange_artefact(artefact, artefacts) {
+                    Some(exchanged) => format!(
+                        "Received {}",
+                        artefacts
+                            .0
+                            .get(&exchanged)
+                            .map_or(exchanged.0.as_str(), |artefact| &artefact.name)
+                    ),
+                    None => "Nothing is offered in exchange".to_string(),
+                };
+                visit.messages.push(message);
+            }
+            WellspringButtonAction::RareCard => {
+                let (Some(cards), Some(class), Some(clans)) = (
+                    cards.get(cards_handle.0.id()),
+                    classes
+                        .get(classes_handle.0.id())
+                        .and_then(|classes| classes.0.get(&run.class)),
+                    clans.get(clans_handle.0.id()),
+                ) else {
+                    log::warn!("Missing asset libraries while drafting a rare card");
+                    continue;
+                };
+                let pool = run.card_pool(class, clans, cards);
+                let rare = pool
+                    .iter()
+                    .filter(|id| {
+                        cards
+                            .0
+                            .get(id)
+                            .is_some_and(|card| card.rarity >= Rarity::Rare)
+                    })
+                    .cloned()
+                    .collect::<Vec<_>>();
+                let drafted = run.draft(if rare.is_empty() { &pool } else { &rare }, 1, cards);
+                visit.used.insert(WellspringService::RareCard);
+                for id in drafted {
+                    if let Some(card) = cards.0.get(&id) {
+                        visit.messages.push(format!("Received {}", card.name));
+                    }
+                    run.deck.push(id.into());
+                }
+            }
+            WellspringButtonAction::Leave => {
+                run.map.advance();
+                save::save(&run);
+                save::checkpoint(&run);
+                app_state.set(AppState::Campaign);
+            }
+        }
+        break;
+    }
+}
+
+fn teardown_wellspring(
+    mut commands: Commands,
+    wellspring_items_query: Query<Entity, With<OnWellspringScreen>>,
+) {
+    for wellspring_entity in &wellspring_items_query {
+        commands.entity(wellspring_entity).despawn();
+    }
+    commands.remove_resource::<WellspringVisit>();
+}
+
+#[derive(Default)]
+pub struct WellspringPlugin;
+
+impl Plugin for WellspringPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(AppState::Wellspring), setup_wellspring)
+            .add_systems(
+                Update,
+                (
+                    spawn_wellspring_ui.run_if(resource_exists_and_changed::<WellspringVisit>),
+                    handle_wellspring_buttons.run_if(resource_exists::<WellspringVisit>),
+                )
+                    .chain()
+                    .run_if(in_state(AppState::Wellspring)),
+            )
+            .add_systems(OnExit(AppState::Wellspring), teardown_wellspring);
+    }
+}
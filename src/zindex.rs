@@ -1,6 +1,10 @@
 pub const Z_BACKGROUND: f32 = 0.;
 
+pub const Z_LOCATION: f32 = 10.;
+
+pub const Z_ENEMY: f32 = 10.;
+
 pub const Z_CARD: f32 = 20.;
 pub const Z_D_CARD_ICON: f32 = 1.;
 
-pub const Z_DEBUG: f32 = 100.;
\ No newline at end of file
+pub const Z_DEBUG: f32 = 100.;